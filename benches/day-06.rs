@@ -0,0 +1,101 @@
+// compares the `AnswerSet` bitmask against the original `HashSet<char>` implementation;
+// run with `cargo bench --bench day-06`
+
+#[path = "../day-06/answers.rs"]
+#[allow(dead_code)]
+mod answers;
+#[path = "harness.rs"]
+mod harness;
+
+use answers::AnswerSet;
+use harness::bench;
+use std::collections::HashSet;
+use std::hint::black_box;
+
+const INPUT: &str = include_str!("../day-06/input.txt");
+
+fn groups(input: &str) -> Vec<Vec<&str>> {
+    input
+        .split("\n\n")
+        .map(|g| g.lines().filter(|l| !l.is_empty()).collect::<Vec<_>>())
+        .filter(|g| !g.is_empty())
+        .collect()
+}
+
+fn count_hashset(groups: &[Vec<HashSet<char>>]) -> (usize, usize) {
+    let anyone = groups
+        .iter()
+        .map(|g| {
+            g.iter()
+                .fold(HashSet::new(), |acc, d| acc.union(d).cloned().collect())
+                .len()
+        })
+        .sum();
+
+    let everyone = groups
+        .iter()
+        .map(|g| {
+            g.iter()
+                .skip(1)
+                .fold(g[0].clone(), |acc, d| {
+                    acc.intersection(d).cloned().collect()
+                })
+                .len()
+        })
+        .sum();
+
+    (anyone, everyone)
+}
+
+fn count_answer_set(groups: &[Vec<AnswerSet>]) -> (usize, usize) {
+    let anyone = groups
+        .iter()
+        .map(|g| g.iter().fold(AnswerSet::new(), |acc, d| acc.union(d)).len())
+        .sum();
+
+    let everyone = groups
+        .iter()
+        .map(|g| {
+            g.iter()
+                .skip(1)
+                .fold(g[0].clone(), |acc, d| acc.intersection(d))
+                .len()
+        })
+        .sum();
+
+    (anyone, everyone)
+}
+
+fn main() {
+    let groups = groups(INPUT);
+
+    let hashsets: Vec<Vec<HashSet<char>>> = groups
+        .iter()
+        .map(|g| g.iter().map(|l| l.chars().collect()).collect())
+        .collect();
+
+    let answer_sets: Vec<Vec<AnswerSet>> = groups
+        .iter()
+        .map(|g| g.iter().map(|l| l.chars().collect()).collect())
+        .collect();
+
+    assert_eq!(count_hashset(&hashsets), count_answer_set(&answer_sets));
+
+    bench("count (HashSet)", || count_hashset(black_box(&hashsets)));
+    bench("count (AnswerSet)", || {
+        count_answer_set(black_box(&answer_sets))
+    });
+
+    bench("parse (HashSet)", || {
+        groups
+            .iter()
+            .map(|g| g.iter().map(|l| l.chars().collect()).collect())
+            .collect::<Vec<Vec<HashSet<char>>>>()
+    });
+    bench("parse (AnswerSet)", || {
+        groups
+            .iter()
+            .map(|g| g.iter().map(|l| l.chars().collect()).collect())
+            .collect::<Vec<Vec<AnswerSet>>>()
+    });
+}
@@ -0,0 +1,132 @@
+// compares bag counting and container queries on `String`-keyed graphs against the interned
+// `Bags` graph on generated layered rule sets; run with `cargo bench --bench day-07`
+
+#[path = "../day-07/bags.rs"]
+#[allow(dead_code)]
+mod bags;
+#[path = "../day-07/graph.rs"]
+#[allow(dead_code)]
+mod graph;
+#[path = "harness.rs"]
+mod harness;
+
+use bags::Bags;
+use graph::Graph;
+use harness::bench;
+use std::collections::{HashMap, HashSet};
+use std::hint::black_box;
+
+fn name(layer: usize, ii: usize) -> String {
+    format!("bag {} {}", layer, ii)
+}
+
+// `depth` layers of `width` bags, each containing one of every bag in the next layer,
+// so there are `width^depth` distinct paths from the top bag (which must fit into a `u64`)
+fn layered(depth: usize, width: usize) -> Graph {
+    let mut graph = Graph::new();
+
+    graph.insert(
+        "top".to_owned(),
+        (0..width).map(|ii| (name(0, ii), 1)).collect(),
+    );
+
+    for layer in 1..depth {
+        for ii in 0..width {
+            let inner = (0..width).map(|jj| (name(layer, jj), 1)).collect();
+            graph.insert(name(layer - 1, ii), inner);
+        }
+    }
+
+    graph
+}
+
+// the original implementation, without memoization
+fn count_bags_naive(from: &str, graph: &Graph) -> u64 {
+    match graph.get(from) {
+        None => 1,
+        Some(bags) => bags.iter().fold(1, |acc, (b, n)| {
+            acc.wrapping_add((*n as u64).wrapping_mul(count_bags_naive(b, graph)))
+        }),
+    }
+}
+
+// memoized counting on the `String`-keyed graph, as before bag names were interned
+fn count_bags_strings(from: &str, graph: &Graph) -> u64 {
+    let mut counts: HashMap<&str, u64> = HashMap::new();
+    let mut stack = vec![(from, false)];
+    let no_bags = vec![];
+
+    while let Some((bag, expanded)) = stack.pop() {
+        let bags = graph.get(bag).unwrap_or(&no_bags);
+
+        if expanded {
+            let total = bags.iter().fold(1u64, |acc, (inner, n)| {
+                acc.wrapping_add((*n as u64).wrapping_mul(counts[&inner[..]]))
+            });
+            counts.insert(bag, total);
+        } else if !counts.contains_key(bag) {
+            stack.push((bag, true));
+            stack.extend(bags.iter().map(|(inner, _)| (&inner[..], false)));
+        }
+    }
+
+    counts[from]
+}
+
+// container query on the `String`-keyed graph, building the reverse index first
+fn containers_strings<'a>(bag: &str, graph: &'a Graph) -> HashSet<&'a str> {
+    let mut reverse: HashMap<&str, Vec<&str>> = HashMap::new();
+
+    for (outer, bags) in graph {
+        for (inner, _) in bags {
+            reverse.entry(&inner[..]).or_default().push(outer);
+        }
+    }
+
+    let mut found = HashSet::new();
+    let mut stack = vec![bag];
+
+    while let Some(current) = stack.pop() {
+        for outer in reverse.get(current).into_iter().flatten() {
+            if found.insert(*outer) {
+                stack.push(outer);
+            }
+        }
+    }
+
+    found
+}
+
+fn main() {
+    for &(depth, width) in &[(8, 4), (10, 4), (12, 4), (15, 16), (5, 300), (10_000, 1)] {
+        let graph = layered(depth, width);
+        let bags = Bags::new(&graph);
+        let top = bags.id("top").unwrap();
+        let bottom = name(depth - 1, 0);
+        let label = |what| format!("{} (depth {}, width {})", what, depth, width);
+
+        assert_eq!(bags.count_bags(top), Ok(count_bags_strings("top", &graph)));
+        assert_eq!(
+            bags.containers(bags.id(&bottom).unwrap()).len(),
+            containers_strings(&bottom, &graph).len()
+        );
+
+        if depth <= 12 && width <= 4 {
+            bench(&label("count naive"), || {
+                count_bags_naive(black_box("top"), &graph)
+            });
+        }
+
+        bench(&label("count strings"), || {
+            count_bags_strings(black_box("top"), &graph)
+        });
+        bench(&label("count interned"), || bags.count_bags(black_box(top)));
+
+        bench(&label("containers strings"), || {
+            containers_strings(black_box(&bottom), &graph).len()
+        });
+        bench(&label("containers interned"), || {
+            bags.containers(black_box(bags.id(&bottom).unwrap())).len()
+        });
+    }
+}
@@ -0,0 +1,17 @@
+// timing loop shared by the benches, which pull it in with `#[path]`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+// run `f` repeatedly for about a second and report the mean time per run
+pub fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    let mut runs = 0;
+    let start = Instant::now();
+
+    while start.elapsed() < Duration::from_secs(1) {
+        black_box(f());
+        runs += 1;
+    }
+
+    println!("{:<44} {:>12.2?}/iter", name, start.elapsed() / runs);
+}
@@ -0,0 +1,22 @@
+// `raw` as a single CSV field, quoted if it contains separators, quotes or line breaks
+pub fn field(raw: &str) -> String {
+    if raw.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", raw.replace('"', "\"\""))
+    } else {
+        raw.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field() {
+        assert_eq!(field(""), "");
+        assert_eq!(field("abc"), "abc");
+        assert_eq!(field("a,b"), "\"a,b\"");
+        assert_eq!(field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(field("a\r\nb"), "\"a\r\nb\"");
+    }
+}
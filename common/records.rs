@@ -0,0 +1,119 @@
+use std::io::{self, BufRead};
+
+// a run of consecutive non-blank lines of the input
+#[derive(Debug, PartialEq)]
+pub struct Group {
+    pub first_line: usize,
+    pub lines: Vec<String>,
+}
+
+impl Group {
+    pub fn last_line(&self) -> usize {
+        self.first_line + self.lines.len() - 1
+    }
+}
+
+pub struct Groups<R> {
+    lines: io::Lines<R>,
+    line: usize,
+}
+
+// split `reader` into groups separated by one or more blank (or whitespace-only) lines,
+// numbering lines from 1; trailing whitespace, including `\r`, is removed from each line
+pub fn groups<R: BufRead>(reader: R) -> Groups<R> {
+    Groups {
+        lines: reader.lines(),
+        line: 0,
+    }
+}
+
+impl<R: BufRead> Iterator for Groups<R> {
+    type Item = io::Result<Group>;
+
+    fn next(&mut self) -> Option<io::Result<Group>> {
+        let mut group: Option<Group> = None;
+
+        loop {
+            let line = match self.lines.next() {
+                None => return group.map(Ok),
+                Some(Err(e)) => return Some(Err(e)),
+                Some(Ok(line)) => line,
+            };
+
+            self.line += 1;
+            let line = line.trim_end();
+
+            if line.is_empty() {
+                match group {
+                    None => continue,
+                    Some(group) => return Some(Ok(group)),
+                }
+            }
+
+            let first_line = self.line;
+
+            group
+                .get_or_insert_with(|| Group {
+                    first_line,
+                    lines: vec![],
+                })
+                .lines
+                .push(line.to_owned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read(input: &str) -> Vec<Group> {
+        groups(Cursor::new(input)).map(Result::unwrap).collect()
+    }
+
+    fn group(first_line: usize, lines: &[&str]) -> Group {
+        Group {
+            first_line,
+            lines: lines.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_groups() {
+        assert_eq!(
+            read("a b\nc\n\nd\n"),
+            vec![group(1, &["a b", "c"]), group(4, &["d"])]
+        );
+        assert_eq!(read("a\n\nb"), vec![group(1, &["a"]), group(3, &["b"])]);
+        assert_eq!(read(""), vec![]);
+        assert_eq!(read("\n\n"), vec![]);
+    }
+
+    #[test]
+    fn test_groups_blank_lines() {
+        assert_eq!(
+            read("\n\na\n\n\n \t\nb\nc\n\n"),
+            vec![group(3, &["a"]), group(7, &["b", "c"])]
+        );
+    }
+
+    #[test]
+    fn test_groups_crlf() {
+        assert_eq!(
+            read("a \r\nb\r\n\r\nc\r\n"),
+            vec![group(1, &["a", "b"]), group(4, &["c"])]
+        );
+        assert_eq!(
+            read("a\r\n  \r\nc"),
+            vec![group(1, &["a"]), group(3, &["c"])]
+        );
+    }
+
+    #[test]
+    fn test_last_line() {
+        let group = groups(Cursor::new("\na\nb\n\nc")).next().unwrap().unwrap();
+        assert_eq!(group.first_line, 2);
+        assert_eq!(group.last_line(), 3);
+    }
+}
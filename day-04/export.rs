@@ -0,0 +1,147 @@
+use crate::schema::Schema;
+use advent_of_code_2020::csv;
+use serde_json::{Map, Value};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    JsonLines,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Format, String> {
+        match raw {
+            "jsonl" => Ok(Format::JsonLines),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unexpected export format: {}", raw)),
+        }
+    }
+}
+
+fn csv_field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => csv::field(s),
+        v => csv::field(&v.to_string()),
+    }
+}
+
+pub struct Exporter<W: Write> {
+    format: Format,
+    columns: Vec<String>,
+    out: W,
+}
+
+impl<W: Write> Exporter<W> {
+    pub fn new(format: Format, schema: &Schema, mut out: W) -> io::Result<Exporter<W>> {
+        let columns: Vec<_> = schema.fields.iter().map(|f| f.name.clone()).collect();
+
+        if format == Format::Csv {
+            writeln!(out, "record,valid,error,{}", columns.join(","))?;
+        }
+
+        Ok(Exporter {
+            format,
+            columns,
+            out,
+        })
+    }
+
+    // `values` are the normalized schema fields, or the reason the record could not be parsed
+    pub fn write(
+        &mut self,
+        record: usize,
+        valid: bool,
+        values: Result<Vec<(&str, Value)>, String>,
+    ) -> io::Result<()> {
+        let (values, error) = match values {
+            Ok(values) => (values, Value::Null),
+            Err(e) => {
+                let values = self.columns.iter().map(|c| (&c[..], Value::Null)).collect();
+                (values, Value::from(e))
+            }
+        };
+
+        match self.format {
+            Format::JsonLines => {
+                let mut object = Map::new();
+                object.insert("record".to_owned(), Value::from(record));
+                object.insert("valid".to_owned(), Value::from(valid));
+                object.insert("error".to_owned(), error);
+
+                for (name, value) in values {
+                    object.insert(name.to_owned(), value);
+                }
+
+                writeln!(self.out, "{}", Value::Object(object))
+            }
+            Format::Csv => {
+                let fields: Vec<_> = values.iter().map(|(_, v)| csv_field(v)).collect();
+                let error = csv_field(&error);
+                writeln!(
+                    self.out,
+                    "{},{},{},{}",
+                    record,
+                    valid,
+                    error,
+                    fields.join(",")
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passport::Passport;
+
+    fn export(format: Format, lines: &[&str]) -> String {
+        let schema = Schema::passport();
+        let lines: Vec<_> = lines.iter().map(|l| l.to_string()).collect();
+        let passport = Passport::parse(1, &lines).unwrap();
+
+        let mut out = vec![];
+        let mut exporter = Exporter::new(format, &schema, &mut out).unwrap();
+        let valid = schema.validate(&passport).is_valid();
+        let values = schema.normalize(&passport);
+        exporter.write(1, valid, Ok(values)).unwrap();
+
+        let error = Passport::parse(5, &["byr:1980 byr".to_owned()]).unwrap_err();
+        exporter.write(2, false, Err(error.to_string())).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_export_json_lines() {
+        assert_eq!(
+            export(
+                Format::JsonLines,
+                &["pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623A2F"]
+            ),
+            concat!(
+                r##"{"record":1,"valid":true,"error":null,"byr":1980,"iyr":2012,"eyr":2030,"hgt":187.96,"hcl":"#623a2f","ecl":"grn","pid":"087499704","cid":null}"##,
+                "\n",
+                r##"{"record":2,"valid":false,"error":"line 5, column 10: malformed field `byr`: missing ':'","byr":null,"iyr":null,"eyr":null,"hgt":null,"hcl":null,"ecl":null,"pid":null,"cid":null}"##,
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_export_csv() {
+        assert_eq!(
+            export(Format::Csv, &["byr:19x0 hgt:170 cid:\"1,2\" ecl:grn"]),
+            concat!(
+                "record,valid,error,byr,iyr,eyr,hgt,hcl,ecl,pid,cid\n",
+                "1,false,,19x0,,,170,,grn,,\"\"\"1,2\"\"\"\n",
+                "2,false,\"line 5, column 10: malformed field `byr`: missing ':'\",,,,,,,,\n",
+            )
+        );
+    }
+}
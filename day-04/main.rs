@@ -1,284 +1,126 @@
-use std::collections::HashMap;
-use std::io::{self, BufRead};
-
-pub struct Passport(HashMap<String, String>);
+mod export;
+mod passport;
+mod quantity;
+mod schema;
+
+use advent_of_code_2020::records;
+use export::{Exporter, Format};
+use passport::Passport;
+use schema::Schema;
+use std::env;
+use std::fs;
+use std::io;
+
+#[derive(Default)]
+struct Options {
+    report: bool,
+    export: Option<Format>,
+    schema: Option<String>,
+}
 
-impl From<Vec<String>> for Passport {
-    fn from(raw: Vec<String>) -> Passport {
-        let mut map = HashMap::new();
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = env::args().skip(1);
 
-        for line in raw {
-            for part in line.split_whitespace() {
-                let mut entry = part.split(":");
-                let field = entry.next().expect("has field name").to_owned();
-                let value = entry.next().expect("has field value").to_owned();
-                map.insert(field, value);
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--report" => options.report = true,
+            "--export" => {
+                let format = args.next().ok_or("Missing export format")?;
+                options.export = Some(format.parse()?);
             }
+            _ => options.schema = Some(arg),
         }
-
-        Passport(map)
-    }
-}
-
-impl Passport {
-    fn get(&self, field: &str) -> Option<&String> {
-        self.0.get(field)
-    }
-}
-
-fn is_valid_year(value: &str, min: u32, max: u32) -> bool {
-    if value.len() != 4 {
-        return false;
     }
 
-    match value.parse::<u32>() {
-        Err(_) => false,
-        Ok(v) if v < min || v > max => false,
-        _ => true,
-    }
+    Ok(options)
 }
 
-fn is_valid_byr(value: Option<&str>) -> bool {
-    match value {
-        None => false,
-        Some(v) => is_valid_year(v, 1920, 2002),
+fn read_schema(path: Option<&String>) -> io::Result<Schema> {
+    match path {
+        None => Ok(Schema::passport()),
+        Some(path) => Schema::from_toml(&fs::read_to_string(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
     }
 }
 
-fn is_valid_iyr(value: Option<&str>) -> bool {
-    match value {
-        None => false,
-        Some(v) => is_valid_year(v, 2010, 2020),
+// reports go to stderr while exporting, so they do not end up in the exported records
+fn print_report(exporting: bool, report: String) {
+    if exporting {
+        eprintln!("{}", report);
+    } else {
+        println!("{}", report);
     }
 }
 
-fn is_valid_eyr(value: Option<&str>) -> bool {
-    match value {
-        None => false,
-        Some(v) => is_valid_year(v, 2020, 2030),
-    }
-}
+fn main() -> io::Result<()> {
+    let options = parse_args().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let schema = read_schema(options.schema.as_ref())?;
+    let stdin = io::stdin();
+    let stdout = io::stdout();
 
-fn is_valid_hgt(value: Option<&str>) -> bool {
-    let v = match value {
-        None => return false,
-        Some(v) => v,
+    let mut exporter = match options.export {
+        None => None,
+        Some(format) => Some(Exporter::new(format, &schema, stdout.lock())?),
     };
 
-    let chars: Vec<_> = v.chars().collect();
-
-    match &chars[..] {
-        [_, _, _, 'c', 'm'] => match v[0..3].parse::<u8>() {
-            Err(_) => false,
-            Ok(h) if h < 150 || h > 193 => false,
-            _ => true,
-        },
-        [_, _, 'i', 'n'] => match v[0..2].parse::<u8>() {
-            Err(_) => false,
-            Ok(h) if h < 59 || h > 76 => false,
-            _ => true,
-        },
-        _ => false,
-    }
-}
-
-fn is_valid_hcl(value: Option<&str>) -> bool {
-    match value {
-        None => false,
-        Some(v) => {
-            let chars: Vec<_> = v.chars().collect();
-
-            if chars.len() != 7 {
-                return false;
-            }
-
-            if chars[0] != '#' {
-                return false;
-            }
-
-            if !chars[1..].iter().all(|ch| ch.is_digit(16)) {
-                return false;
-            }
-
-            true
-        }
-    }
-}
-
-fn is_valid_ecl(value: Option<&str>) -> bool {
-    match value {
-        None => false,
-        Some("amb") | Some("blu") | Some("brn") | Some("gry") | Some("grn") | Some("hzl")
-        | Some("oth") => true,
-        _ => false,
-    }
-}
-
-fn is_valid_pid(value: Option<&str>) -> bool {
-    match value {
-        None => false,
-        Some(v) => {
-            let chars: Vec<_> = v.chars().collect();
-
-            if chars.len() != 9 {
-                return false;
-            }
-
-            if !chars.iter().all(|ch| ch.is_digit(10)) {
-                return false;
-            }
-
-            true
-        }
-    }
-}
-
-fn is_valid_cid(_value: Option<&str>) -> bool {
-    true
-}
-
-fn is_valid_1(passport: &Passport) -> bool {
-    passport.get("byr").is_some()
-        && passport.get("iyr").is_some()
-        && passport.get("eyr").is_some()
-        && passport.get("hgt").is_some()
-        && passport.get("hcl").is_some()
-        && passport.get("ecl").is_some()
-        && passport.get("pid").is_some()
-}
-
-fn is_valid_2(passport: &Passport) -> bool {
-    is_valid_byr(passport.get("byr").map(|s| &s[..]))
-        && is_valid_iyr(passport.get("iyr").map(|s| &s[..]))
-        && is_valid_eyr(passport.get("eyr").map(|s| &s[..]))
-        && is_valid_hgt(passport.get("hgt").map(|s| &s[..]))
-        && is_valid_hcl(passport.get("hcl").map(|s| &s[..]))
-        && is_valid_ecl(passport.get("ecl").map(|s| &s[..]))
-        && is_valid_pid(passport.get("pid").map(|s| &s[..]))
-        && is_valid_cid(passport.get("cid").map(|s| &s[..]))
-}
-
-fn main() {
-    let stdin = io::stdin();
-
     let mut count_1 = 0;
     let mut count_2 = 0;
 
-    let mut lines = stdin.lock().lines().map(|l| l.unwrap());
+    for (record, group) in records::groups(stdin.lock()).enumerate() {
+        let group = group?;
+        let record = record + 1;
+        let first_line = group.first_line;
+        let last_line = group.last_line();
+
+        let passport = match Passport::parse(first_line, &group.lines) {
+            Ok(passport) => passport,
+            Err(e) => {
+                if let Some(exporter) = &mut exporter {
+                    exporter.write(record, false, Err(e.to_string()))?;
+                }
+
+                if options.report {
+                    print_report(
+                        exporter.is_some(),
+                        format!(
+                            "passport #{} (lines {}-{}):\n{}\n",
+                            record, first_line, last_line, e
+                        ),
+                    );
+                }
+
+                continue;
+            }
+        };
 
-    loop {
-        let raw = lines.by_ref().take_while(|l| l != "").collect::<Vec<_>>();
+        let report = schema.validate(&passport);
 
-        if raw.is_empty() {
-            break;
+        if report.has_required_fields() {
+            count_1 += 1;
         }
 
-        let passport = Passport::from(raw);
-
-        if is_valid_1(&passport) {
-            count_1 += 1;
+        if let Some(exporter) = &mut exporter {
+            exporter.write(record, report.is_valid(), Ok(schema.normalize(&passport)))?;
         }
 
-        if is_valid_2(&passport) {
+        if report.is_valid() {
             count_2 += 1;
+        } else if options.report {
+            print_report(
+                exporter.is_some(),
+                format!(
+                    "passport #{} (lines {}-{}):\n{}",
+                    record, first_line, last_line, report
+                ),
+            );
         }
     }
 
-    println!("puzzle #1 = {:?}", count_1);
-    println!("puzzle #2 = {:?}", count_2);
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_is_valid_byr() {
-        assert_eq!(is_valid_byr(Some("03")), false);
-        assert_eq!(is_valid_byr(Some("1919")), false);
-        assert_eq!(is_valid_byr(Some("1920")), true);
-        assert_eq!(is_valid_byr(Some("2002")), true);
-        assert_eq!(is_valid_byr(Some("2003")), false);
-        assert_eq!(is_valid_byr(Some("02002")), false);
-        assert_eq!(is_valid_byr(Some("2O2O")), false);
-        assert_eq!(is_valid_byr(None), false);
-    }
-
-    #[test]
-    fn test_is_valid_iyr() {
-        assert_eq!(is_valid_iyr(Some("03")), false);
-        assert_eq!(is_valid_iyr(Some("2009")), false);
-        assert_eq!(is_valid_iyr(Some("2010")), true);
-        assert_eq!(is_valid_iyr(Some("2020")), true);
-        assert_eq!(is_valid_iyr(Some("2021")), false);
-        assert_eq!(is_valid_iyr(Some("02020")), false);
-        assert_eq!(is_valid_iyr(Some("2O2O")), false);
-        assert_eq!(is_valid_iyr(None), false);
-    }
-
-    #[test]
-    fn test_is_valid_eyr() {
-        assert_eq!(is_valid_eyr(Some("03")), false);
-        assert_eq!(is_valid_eyr(Some("2019")), false);
-        assert_eq!(is_valid_eyr(Some("2020")), true);
-        assert_eq!(is_valid_eyr(Some("2030")), true);
-        assert_eq!(is_valid_eyr(Some("2031")), false);
-        assert_eq!(is_valid_eyr(Some("02030")), false);
-        assert_eq!(is_valid_eyr(Some("2O2O")), false);
-        assert_eq!(is_valid_eyr(None), false);
-    }
-
-    #[test]
-    fn test_is_valid_hgt() {
-        assert_eq!(is_valid_hgt(Some("149cm")), false);
-        assert_eq!(is_valid_hgt(Some("150cm")), true);
-        assert_eq!(is_valid_hgt(Some("193cm")), true);
-        assert_eq!(is_valid_hgt(Some("194cm")), false);
-        assert_eq!(is_valid_hgt(Some("150c")), false);
-        assert_eq!(is_valid_hgt(Some("cm")), false);
-
-        assert_eq!(is_valid_hgt(Some("58in")), false);
-        assert_eq!(is_valid_hgt(Some("59in")), true);
-        assert_eq!(is_valid_hgt(Some("76in")), true);
-        assert_eq!(is_valid_hgt(Some("77in")), false);
-        assert_eq!(is_valid_hgt(Some("76i")), false);
-        assert_eq!(is_valid_hgt(Some("in")), false);
-        assert_eq!(is_valid_hgt(None), false);
+    if exporter.is_none() {
+        println!("puzzle #1 = {:?}", count_1);
+        println!("puzzle #2 = {:?}", count_2);
     }
 
-    #[test]
-    fn test_is_valid_hcl() {
-        assert_eq!(is_valid_hcl(Some("#000000")), true);
-        assert_eq!(is_valid_hcl(Some("#ffffff")), true);
-        assert_eq!(is_valid_hcl(Some("#123abc")), true);
-
-        assert_eq!(is_valid_hcl(Some("#123abz")), false);
-        assert_eq!(is_valid_hcl(Some("123abc")), false);
-        assert_eq!(is_valid_hcl(Some("#12345")), false);
-    }
-
-    #[test]
-    fn test_is_valid_ecl() {
-        assert_eq!(is_valid_ecl(Some("amb")), true);
-        assert_eq!(is_valid_ecl(Some("blu")), true);
-        assert_eq!(is_valid_ecl(Some("brn")), true);
-        assert_eq!(is_valid_ecl(Some("gry")), true);
-        assert_eq!(is_valid_ecl(Some("grn")), true);
-        assert_eq!(is_valid_ecl(Some("hzl")), true);
-        assert_eq!(is_valid_ecl(Some("oth")), true);
-
-        assert_eq!(is_valid_ecl(Some("wat")), false);
-        assert_eq!(is_valid_ecl(None), false);
-    }
-
-    #[test]
-    fn test_is_valid_pid() {
-        assert_eq!(is_valid_pid(Some("000000001")), true);
-        assert_eq!(is_valid_pid(Some("999999999")), true);
-
-        assert_eq!(is_valid_pid(Some("0")), false);
-        assert_eq!(is_valid_pid(Some("09")), false);
-        assert_eq!(is_valid_pid(Some("0123456789")), false);
-        assert_eq!(is_valid_pid(None), false);
-    }
+    Ok(())
 }
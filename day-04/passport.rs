@@ -0,0 +1,235 @@
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub value: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Malformed {
+        token: String,
+        reason: &'static str,
+        line: usize,
+        column: usize,
+    },
+    Duplicate {
+        name: String,
+        line: usize,
+        column: usize,
+        first_line: usize,
+        first_column: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Malformed {
+                token,
+                reason,
+                line,
+                column,
+            } => write!(
+                f,
+                "line {}, column {}: malformed field `{}`: {}",
+                line, column, token, reason
+            ),
+            ParseError::Duplicate {
+                name,
+                line,
+                column,
+                first_line,
+                first_column,
+            } => write!(
+                f,
+                "line {}, column {}: duplicate field `{}` (first defined at line {}, column {})",
+                line, column, name, first_line, first_column
+            ),
+        }
+    }
+}
+
+// split `line` on whitespace, keeping the byte offset of each token
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = vec![];
+    let mut start = None;
+
+    for (ii, ch) in line.char_indices() {
+        match (start, ch.is_whitespace()) {
+            (None, false) => start = Some(ii),
+            (Some(s), true) => {
+                tokens.push((s, &line[s..ii]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(s) = start {
+        tokens.push((s, &line[s..]));
+    }
+
+    tokens
+}
+
+fn parse_entry(token: &str, line: usize, column: usize) -> Result<Entry, ParseError> {
+    let malformed = |reason| ParseError::Malformed {
+        token: token.to_owned(),
+        reason,
+        line,
+        column,
+    };
+
+    match token.split(':').collect::<Vec<_>>()[..] {
+        [_] => Err(malformed("missing ':'")),
+        ["", _] => Err(malformed("empty field name")),
+        [_, ""] => Err(malformed("empty field value")),
+        [name, value] => Ok(Entry {
+            name: name.to_owned(),
+            value: value.to_owned(),
+            line,
+            column,
+        }),
+        _ => Err(malformed("more than one ':'")),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Passport(Vec<Entry>);
+
+impl Passport {
+    // `lines` are consecutive lines of the input, the first of which is `first_line`
+    pub fn parse(first_line: usize, lines: &[String]) -> Result<Passport, ParseError> {
+        let mut entries: Vec<Entry> = vec![];
+
+        for (ii, raw) in lines.iter().enumerate() {
+            let line = first_line + ii;
+
+            for (offset, token) in tokenize(raw) {
+                let column = raw[..offset].chars().count() + 1;
+                let entry = parse_entry(token, line, column)?;
+
+                if let Some(first) = entries.iter().find(|e| e.name == entry.name) {
+                    return Err(ParseError::Duplicate {
+                        name: entry.name,
+                        line,
+                        column,
+                        first_line: first.line,
+                        first_column: first.column,
+                    });
+                }
+
+                entries.push(entry);
+            }
+        }
+
+        Ok(Passport(entries))
+    }
+
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|e| e.name == field)
+            .map(|e| &e.value[..])
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn parse(lines: &[&str]) -> Result<Passport, ParseError> {
+        let lines: Vec<_> = lines.iter().map(|l| l.to_string()).collect();
+        Passport::parse(1, &lines)
+    }
+
+    #[test]
+    fn test_parse() {
+        let passport = parse(&["ecl:gry pid:860033327", " eyr:2020\thcl:#fffffd"]).unwrap();
+
+        let fields: Vec<_> = passport
+            .entries()
+            .iter()
+            .map(|e| (&e.name[..], &e.value[..], e.line, e.column))
+            .collect();
+
+        assert_eq!(
+            fields,
+            vec![
+                ("ecl", "gry", 1, 1),
+                ("pid", "860033327", 1, 9),
+                ("eyr", "2020", 2, 2),
+                ("hcl", "#fffffd", 2, 11),
+            ]
+        );
+
+        assert_eq!(passport.get("pid"), Some("860033327"));
+        assert_eq!(passport.get("cid"), None);
+        assert_eq!(parse(&[]), Ok(Passport(vec![])));
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let malformed = |token: &str, reason, line, column| {
+            Err(ParseError::Malformed {
+                token: token.to_owned(),
+                reason,
+                line,
+                column,
+            })
+        };
+
+        assert_eq!(parse(&["byr"]), malformed("byr", "missing ':'", 1, 1));
+        assert_eq!(
+            parse(&["ecl:gry", "ü byr:"]),
+            malformed("ü", "missing ':'", 2, 1)
+        );
+        assert_eq!(
+            parse(&["ecl:gry ü:1 byr:"]),
+            malformed("byr:", "empty field value", 1, 13)
+        );
+        assert_eq!(
+            parse(&[":1920"]),
+            malformed(":1920", "empty field name", 1, 1)
+        );
+        assert_eq!(
+            parse(&["hgt:1:2"]),
+            malformed("hgt:1:2", "more than one ':'", 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_parse_duplicate() {
+        assert_eq!(
+            parse(&["byr:1920 ecl:gry", "iyr:2010 byr:2002"]),
+            Err(ParseError::Duplicate {
+                name: "byr".to_owned(),
+                line: 2,
+                column: 10,
+                first_line: 1,
+                first_column: 1,
+            })
+        );
+
+        assert_eq!(
+            parse(&["byr:1920 byr:1920"]).unwrap_err().to_string(),
+            "line 1, column 10: duplicate field `byr` (first defined at line 1, column 1)"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_parse(lines in prop::collection::vec(any::<String>(), 0..4)) {
+            let _ = Passport::parse(1, &lines);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct Quantity<'a> {
+    pub amount: i64,
+    pub unit: &'a str,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum QuantityError {
+    MissingAmount,
+    AmountTooLarge,
+}
+
+impl fmt::Display for QuantityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QuantityError::MissingAmount => write!(f, "is missing a number"),
+            QuantityError::AmountTooLarge => write!(f, "has a number that is too large"),
+        }
+    }
+}
+
+impl<'a> Quantity<'a> {
+    // a run of ASCII digits followed by a (possibly empty) unit, e.g. `183cm`
+    pub fn parse(raw: &'a str) -> Result<Quantity<'a>, QuantityError> {
+        let split = raw
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(raw.len());
+
+        // `split` is always on a char boundary as digits are single-byte
+        let (amount, unit) = raw.split_at(split);
+
+        if amount.is_empty() {
+            return Err(QuantityError::MissingAmount);
+        }
+
+        let amount = amount.parse().map_err(|_| QuantityError::AmountTooLarge)?;
+
+        Ok(Quantity { amount, unit })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_parse() {
+        let quantity = |amount, unit| Ok(Quantity { amount, unit });
+
+        assert_eq!(Quantity::parse("183cm"), quantity(183, "cm"));
+        assert_eq!(Quantity::parse("59in"), quantity(59, "in"));
+        assert_eq!(Quantity::parse("7in"), quantity(7, "in"));
+        assert_eq!(Quantity::parse("0099in"), quantity(99, "in"));
+        assert_eq!(Quantity::parse("170"), quantity(170, ""));
+        assert_eq!(Quantity::parse("150c"), quantity(150, "c"));
+        assert_eq!(Quantity::parse("1ü2"), quantity(1, "ü2"));
+
+        assert_eq!(Quantity::parse(""), Err(QuantityError::MissingAmount));
+        assert_eq!(Quantity::parse("cm"), Err(QuantityError::MissingAmount));
+        assert_eq!(Quantity::parse("ü1cm"), Err(QuantityError::MissingAmount));
+        assert_eq!(Quantity::parse("-1cm"), Err(QuantityError::MissingAmount));
+        assert_eq!(
+            Quantity::parse("99999999999999999999cm"),
+            Err(QuantityError::AmountTooLarge)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_parse(raw in any::<String>()) {
+            let _ = Quantity::parse(&raw);
+        }
+
+        #[test]
+        fn fuzz_parse_round_trip(amount in 0..=i64::MAX, unit in "[^0-9]*") {
+            let raw = format!("{}{}", amount, unit);
+            prop_assert_eq!(Quantity::parse(&raw), Ok(Quantity { amount, unit: &unit }));
+        }
+    }
+}
@@ -0,0 +1,523 @@
+use crate::passport::Passport;
+use crate::quantity::Quantity;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Debug, Deserialize)]
+pub struct Range {
+    min: i64,
+    max: i64,
+}
+
+impl Range {
+    fn check(&self, value: i64, raw: &str) -> Result<(), String> {
+        match value {
+            v if v < self.min => Err(format!("{} is below {}", raw, self)),
+            v if v > self.max => Err(format!("{} exceeds {}", raw, self)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}..={}", self.min, self.max)
+    }
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Unit {
+    #[serde(flatten)]
+    range: Range,
+    // size of this unit relative to the others; amounts are converted to the canonical unit
+    // by the ratio of their factors
+    #[serde(default = "one")]
+    factor: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Pattern(Regex);
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(raw: String) -> Result<Pattern, regex::Error> {
+        Regex::new(&raw).map(Pattern)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Constraint {
+    Any,
+    Int {
+        digits: Option<usize>,
+        #[serde(flatten)]
+        range: Range,
+    },
+    Quantity {
+        units: BTreeMap<String, Unit>,
+        canonical: Option<String>,
+    },
+    Regex {
+        pattern: Pattern,
+    },
+    Enum {
+        values: Vec<String>,
+    },
+}
+
+impl Constraint {
+    fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            Constraint::Any => Ok(()),
+            Constraint::Int { digits, range } => {
+                if let Some(d) = digits {
+                    if value.chars().count() != *d {
+                        return Err(format!("{} does not have {} digits", value, d));
+                    }
+                }
+
+                match parse_int(value) {
+                    None => Err(format!("{} is not a number", value)),
+                    Some(v) => range.check(v, value),
+                }
+            }
+            Constraint::Quantity { units, .. } => {
+                let quantity = Quantity::parse(value).map_err(|e| format!("{} {}", value, e))?;
+
+                let unit = units.get(quantity.unit).ok_or_else(|| {
+                    let expected = units.keys().cloned().collect::<Vec<_>>().join(", ");
+                    format!("{} does not have a unit of {}", value, expected)
+                })?;
+
+                unit.range.check(quantity.amount, value)
+            }
+            Constraint::Regex { pattern } => match pattern.0.is_match(value) {
+                true => Ok(()),
+                false => Err(format!("{} does not match {}", value, pattern.0)),
+            },
+            Constraint::Enum { values } => match values.iter().any(|v| v == value) {
+                true => Ok(()),
+                false => Err(format!("{} is not one of {}", value, values.join(", "))),
+            },
+        }
+    }
+
+    fn normalize(&self, value: &str) -> Value {
+        match self {
+            Constraint::Int { .. } => match parse_int(value) {
+                Some(v) => Value::from(v),
+                None => Value::from(value),
+            },
+            Constraint::Quantity {
+                units,
+                canonical: Some(canonical),
+            } => {
+                let quantity = match Quantity::parse(value) {
+                    Ok(q) => q,
+                    Err(_) => return Value::from(value),
+                };
+
+                match (units.get(quantity.unit), units.get(canonical)) {
+                    (Some(unit), Some(canonical)) => {
+                        let factor = unit.factor / canonical.factor;
+                        let converted = (quantity.amount as f64 * factor * 100.0).round() / 100.0;
+
+                        match converted.fract() == 0.0 {
+                            true => Value::from(converted as i64),
+                            false => Value::from(converted),
+                        }
+                    }
+                    _ => Value::from(value),
+                }
+            }
+            _ => Value::from(value),
+        }
+    }
+}
+
+fn parse_int(raw: &str) -> Option<i64> {
+    if raw.is_empty() || !raw.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+
+    raw.parse().ok()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Field {
+    pub name: String,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub lowercase: bool,
+    #[serde(flatten)]
+    pub constraint: Constraint,
+}
+
+impl Field {
+    fn normalize(&self, value: &str) -> Value {
+        match self.lowercase {
+            true => self.constraint.normalize(&value.to_lowercase()),
+            false => self.constraint.normalize(value),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Invalid {
+    pub field: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    pub missing: Vec<String>,
+    pub invalid: Vec<Invalid>,
+    pub unknown: Vec<String>,
+}
+
+impl ValidationReport {
+    pub fn has_required_fields(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.invalid.is_empty() && self.unknown.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for field in &self.missing {
+            writeln!(f, "{}: missing", field)?;
+        }
+
+        for Invalid { field, reason } in &self.invalid {
+            writeln!(f, "{}: {}", field, reason)?;
+        }
+
+        for field in &self.unknown {
+            writeln!(f, "{}: unknown field", field)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Schema {
+    #[serde(rename = "field")]
+    pub fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn from_toml(raw: &str) -> Result<Schema, String> {
+        let schema: Schema = toml::from_str(raw).map_err(|e| format!("Invalid schema: {}", e))?;
+
+        for field in &schema.fields {
+            if let Constraint::Quantity {
+                units,
+                canonical: Some(canonical),
+            } = &field.constraint
+            {
+                if !units.contains_key(canonical) {
+                    return Err(format!(
+                        "Invalid schema: canonical unit {} of {} is not one of its units",
+                        canonical, field.name
+                    ));
+                }
+
+                if let Some((name, _)) = units
+                    .iter()
+                    .find(|(_, u)| !u.factor.is_finite() || u.factor <= 0.0)
+                {
+                    return Err(format!(
+                        "Invalid schema: unit {} of {} does not have a positive factor",
+                        name, field.name
+                    ));
+                }
+            }
+        }
+
+        Ok(schema)
+    }
+
+    pub fn passport() -> Schema {
+        Schema::from_toml(include_str!("passport.toml")).expect("built-in schema is valid")
+    }
+
+    pub fn validate(&self, passport: &Passport) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        for field in &self.fields {
+            match passport.get(&field.name) {
+                None if field.required => report.missing.push(field.name.clone()),
+                None => {}
+                Some(value) => {
+                    if let Err(reason) = field.constraint.check(value) {
+                        report.invalid.push(Invalid {
+                            field: field.name.clone(),
+                            reason,
+                        });
+                    }
+                }
+            }
+        }
+
+        for entry in passport.entries() {
+            if !self.fields.iter().any(|f| f.name == entry.name) {
+                report.unknown.push(entry.name.clone());
+            }
+        }
+
+        report
+    }
+
+    // values of all schema fields in schema order, `null` if missing
+    pub fn normalize(&self, passport: &Passport) -> Vec<(&str, Value)> {
+        self.fields
+            .iter()
+            .map(|f| {
+                let value = passport
+                    .get(&f.name)
+                    .map_or(Value::Null, |v| f.normalize(v));
+                (&f.name[..], value)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn is_valid(schema: &Schema, name: &str, value: Option<&str>) -> bool {
+        let raw = value.map(|v| format!("{}:{}", name, v));
+        let report =
+            schema.validate(&Passport::parse(1, &raw.into_iter().collect::<Vec<_>>()).unwrap());
+
+        !report.missing.iter().any(|f| f == name) && !report.invalid.iter().any(|i| i.field == name)
+    }
+
+    fn validator(name: &str) -> impl Fn(Option<&str>) -> bool {
+        let schema = Schema::passport();
+        let name = name.to_owned();
+        move |value| is_valid(&schema, &name, value)
+    }
+
+    #[test]
+    fn test_is_valid_byr() {
+        let is_valid_byr = validator("byr");
+        assert!(!is_valid_byr(Some("03")));
+        assert!(!is_valid_byr(Some("1919")));
+        assert!(is_valid_byr(Some("1920")));
+        assert!(is_valid_byr(Some("2002")));
+        assert!(!is_valid_byr(Some("2003")));
+        assert!(!is_valid_byr(Some("02002")));
+        assert!(!is_valid_byr(Some("2O2O")));
+        assert!(!is_valid_byr(None));
+    }
+
+    #[test]
+    fn test_is_valid_iyr() {
+        let is_valid_iyr = validator("iyr");
+        assert!(!is_valid_iyr(Some("03")));
+        assert!(!is_valid_iyr(Some("2009")));
+        assert!(is_valid_iyr(Some("2010")));
+        assert!(is_valid_iyr(Some("2020")));
+        assert!(!is_valid_iyr(Some("2021")));
+        assert!(!is_valid_iyr(Some("02020")));
+        assert!(!is_valid_iyr(Some("2O2O")));
+        assert!(!is_valid_iyr(None));
+    }
+
+    #[test]
+    fn test_is_valid_eyr() {
+        let is_valid_eyr = validator("eyr");
+        assert!(!is_valid_eyr(Some("03")));
+        assert!(!is_valid_eyr(Some("2019")));
+        assert!(is_valid_eyr(Some("2020")));
+        assert!(is_valid_eyr(Some("2030")));
+        assert!(!is_valid_eyr(Some("2031")));
+        assert!(!is_valid_eyr(Some("02030")));
+        assert!(!is_valid_eyr(Some("2O2O")));
+        assert!(!is_valid_eyr(None));
+    }
+
+    #[test]
+    fn test_is_valid_hgt() {
+        let is_valid_hgt = validator("hgt");
+        assert!(!is_valid_hgt(Some("149cm")));
+        assert!(is_valid_hgt(Some("150cm")));
+        assert!(is_valid_hgt(Some("193cm")));
+        assert!(!is_valid_hgt(Some("194cm")));
+        assert!(!is_valid_hgt(Some("150c")));
+        assert!(!is_valid_hgt(Some("cm")));
+
+        assert!(!is_valid_hgt(Some("58in")));
+        assert!(is_valid_hgt(Some("59in")));
+        assert!(is_valid_hgt(Some("76in")));
+        assert!(!is_valid_hgt(Some("77in")));
+        assert!(!is_valid_hgt(Some("76i")));
+        assert!(!is_valid_hgt(Some("in")));
+        assert!(!is_valid_hgt(None));
+    }
+
+    #[test]
+    fn test_is_valid_hcl() {
+        let is_valid_hcl = validator("hcl");
+        assert!(is_valid_hcl(Some("#000000")));
+        assert!(is_valid_hcl(Some("#ffffff")));
+        assert!(is_valid_hcl(Some("#123abc")));
+
+        assert!(!is_valid_hcl(Some("#123abz")));
+        assert!(!is_valid_hcl(Some("123abc")));
+        assert!(!is_valid_hcl(Some("#12345")));
+    }
+
+    #[test]
+    fn test_is_valid_ecl() {
+        let is_valid_ecl = validator("ecl");
+        assert!(is_valid_ecl(Some("amb")));
+        assert!(is_valid_ecl(Some("blu")));
+        assert!(is_valid_ecl(Some("brn")));
+        assert!(is_valid_ecl(Some("gry")));
+        assert!(is_valid_ecl(Some("grn")));
+        assert!(is_valid_ecl(Some("hzl")));
+        assert!(is_valid_ecl(Some("oth")));
+
+        assert!(!is_valid_ecl(Some("wat")));
+        assert!(!is_valid_ecl(None));
+    }
+
+    #[test]
+    fn test_is_valid_pid() {
+        let is_valid_pid = validator("pid");
+        assert!(is_valid_pid(Some("000000001")));
+        assert!(is_valid_pid(Some("999999999")));
+
+        assert!(!is_valid_pid(Some("0")));
+        assert!(!is_valid_pid(Some("09")));
+        assert!(!is_valid_pid(Some("0123456789")));
+        assert!(!is_valid_pid(None));
+    }
+
+    #[test]
+    fn test_is_valid_cid() {
+        let is_valid_cid = validator("cid");
+        assert!(is_valid_cid(Some("147")));
+        assert!(is_valid_cid(None));
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let schema = Schema::from_toml(
+            "[[field]]\nname = \"id\"\nrequired = true\ntype = \"int\"\nmin = 1\nmax = 9",
+        )
+        .unwrap();
+
+        assert!(is_valid(&schema, "id", Some("7")));
+        assert!(!is_valid(&schema, "id", Some("10")));
+        assert!(!is_valid(&schema, "id", None));
+
+        assert!(Schema::from_toml("[[field]]\nname = \"id\"\ntype = \"date\"").is_err());
+        assert!(
+            Schema::from_toml("[[field]]\nname = \"id\"\ntype = \"regex\"\npattern = \"(\"")
+                .is_err()
+        );
+        assert!(Schema::from_toml(
+            "[[field]]\nname = \"h\"\ntype = \"quantity\"\ncanonical = \"m\"\nunits = { cm = { min = 1, max = 2 } }"
+        )
+        .is_err());
+        assert!(Schema::from_toml(
+            "[[field]]\nname = \"h\"\ntype = \"quantity\"\ncanonical = \"cm\"\nunits = { cm = { min = 1, max = 2, factor = 0.0 } }"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_normalize_canonical() {
+        let schema = Schema::from_toml(
+            "[[field]]\nname = \"h\"\ntype = \"quantity\"\ncanonical = \"in\"\nunits = { cm = { min = 1, max = 300 }, in = { min = 1, max = 100, factor = 2.54 } }",
+        )
+        .unwrap();
+        let field = &schema.fields[0];
+
+        assert_eq!(field.normalize("254cm"), Value::from(100));
+        assert_eq!(field.normalize("70in"), Value::from(70));
+        assert_eq!(field.normalize("100cm"), Value::from(39.37));
+        assert_eq!(field.normalize("70ft"), Value::from("70ft"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = Schema::passport();
+
+        let passport = Passport::parse(
+            1,
+            &[
+                "pid:087499704 hgt:194cm ecl:grn iyr:2012 eyr:2030 byr:1980".to_owned(),
+                "hcl:#623a2f".to_owned(),
+            ],
+        )
+        .unwrap();
+
+        let report = schema.validate(&passport);
+        assert!(report.has_required_fields());
+        assert!(!report.is_valid());
+        assert_eq!(report.to_string(), "hgt: 194cm exceeds 150..=193\n");
+
+        let passport = Passport::parse(1, &["ecl:xyz hgt:170 xyz:1".to_owned()]).unwrap();
+
+        let report = schema.validate(&passport);
+        assert_eq!(report.missing, vec!["byr", "iyr", "eyr", "hcl", "pid"]);
+        assert_eq!(
+            report.invalid,
+            vec![
+                Invalid {
+                    field: "hgt".to_owned(),
+                    reason: "170 does not have a unit of cm, in".to_owned(),
+                },
+                Invalid {
+                    field: "ecl".to_owned(),
+                    reason: "xyz is not one of amb, blu, brn, gry, grn, hzl, oth".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(report.unknown, vec!["xyz"]);
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_field_validators(value in any::<String>()) {
+            for field in &Schema::passport().fields {
+                let _ = field.constraint.check(&value);
+                let _ = field.normalize(&value);
+            }
+        }
+
+        #[test]
+        fn fuzz_hgt(amount in 0..1000i64, unit in "cm|in|[a-z]{0,2}") {
+            let expected = match &unit[..] {
+                "cm" => (150..=193).contains(&amount),
+                "in" => (59..=76).contains(&amount),
+                _ => false,
+            };
+
+            let is_valid_hgt = validator("hgt");
+            prop_assert_eq!(is_valid_hgt(Some(&format!("{}{}", amount, unit))), expected);
+        }
+    }
+}
@@ -1,115 +1,536 @@
+mod manifest;
+
+use manifest::Manifest;
+use std::env;
+use std::fmt;
 use std::io::{self, BufRead};
+use std::ops::Range;
+use std::process;
+
+type Ticket = (u32, u32);
+
+// describes how boarding passes encode seats: the first `row_bits` letters select the row,
+// the remaining `col_bits` letters the column, each as a binary number written with the
+// given (low, high) letters
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct SeatLayout {
+    row_bits: u32,
+    col_bits: u32,
+    row_letters: (char, char),
+    col_letters: (char, char),
+}
 
-type Ticket = (u8, u8);
+const STANDARD: SeatLayout = SeatLayout {
+    row_bits: 7,
+    col_bits: 3,
+    row_letters: ('F', 'B'),
+    col_letters: ('L', 'R'),
+};
 
-fn parse_binary(raw: &str) -> Result<u8, String> {
-    if raw.len() > 8 {
-        return Err(format!("Unexpected binary string: {}", raw));
+// seat ids must fit into a `u32`
+const MAX_BITS: u32 = 32;
+
+fn parse_binary(raw: &[char], letters: (char, char)) -> Result<u32, String> {
+    let err = || {
+        format!(
+            "Unexpected binary string: {}",
+            raw.iter().collect::<String>()
+        )
+    };
+
+    if raw.len() > MAX_BITS as usize {
+        return Err(err());
     }
 
-    let mut num = 0;
+    let mut num: u32 = 0;
 
-    for ch in raw.chars() {
-        num = 2 * num
-            + match ch {
-                'F' => 0,
-                'L' => 0,
-                'B' => 1,
-                'R' => 1,
-                _ => return Err(format!("Unexpected binary string: {}", raw)),
-            };
+    for ch in raw {
+        let bit = match *ch {
+            c if c == letters.0 => 0,
+            c if c == letters.1 => 1,
+            _ => return Err(err()),
+        };
+
+        num = (num << 1) | bit;
     }
 
     Ok(num)
 }
 
-fn parse_ticket(raw: &str) -> Result<Ticket, String> {
-    if raw.len() != 10 {
-        return Err(format!("Unexpected ticket format: {}", raw));
+fn encode_binary(num: u32, bits: u32, letters: (char, char)) -> Result<String, String> {
+    if num.checked_shr(bits).unwrap_or(0) != 0 {
+        return Err(format!("Unexpected number for {} bits: {}", bits, num));
+    }
+
+    Ok((0..bits)
+        .rev()
+        .map(|ii| match (num >> ii) & 1 {
+            0 => letters.0,
+            _ => letters.1,
+        })
+        .collect())
+}
+
+impl SeatLayout {
+    fn new(row_bits: u32, col_bits: u32) -> Result<SeatLayout, String> {
+        if row_bits.saturating_add(col_bits) > MAX_BITS {
+            return Err(format!(
+                "Unexpected layout: {} row and {} column bits exceed {}",
+                row_bits, col_bits, MAX_BITS
+            ));
+        }
+
+        Ok(SeatLayout {
+            row_bits,
+            col_bits,
+            ..STANDARD
+        })
     }
 
-    let row = parse_binary(&raw[..7])?;
-    let col = parse_binary(&raw[7..])?;
-    Ok((row, col))
+    fn parse_ticket(&self, raw: &str) -> Result<Ticket, String> {
+        let chars: Vec<_> = raw.chars().collect();
+
+        if chars.len() != (self.row_bits + self.col_bits) as usize {
+            return Err(format!("Unexpected ticket format: {}", raw));
+        }
+
+        let (row, col) = chars.split_at(self.row_bits as usize);
+        let row = parse_binary(row, self.row_letters)?;
+        let col = parse_binary(col, self.col_letters)?;
+        Ok((row, col))
+    }
+
+    fn seat_id(&self, ticket: Ticket) -> u32 {
+        let (row, col) = ticket;
+
+        // `row` is always 0 if there are `MAX_BITS` column bits
+        row.checked_shl(self.col_bits).unwrap_or(0) | col
+    }
+
+    fn ticket(&self, seat_id: u32) -> Result<Ticket, String> {
+        let row = seat_id.checked_shr(self.col_bits).unwrap_or(0);
+        let col = seat_id & !u32::MAX.checked_shl(self.col_bits).unwrap_or(0);
+
+        if row.checked_shr(self.row_bits).unwrap_or(0) != 0 {
+            return Err(format!("Unexpected seat id: {}", seat_id));
+        }
+
+        Ok((row, col))
+    }
+
+    fn encode_ticket(&self, ticket: Ticket) -> Result<String, String> {
+        let (row, col) = ticket;
+        let row = encode_binary(row, self.row_bits, self.row_letters)?;
+        let col = encode_binary(col, self.col_bits, self.col_letters)?;
+        Ok(row + &col)
+    }
+
+    fn encode_seat_id(&self, seat_id: u32) -> Result<String, String> {
+        self.encode_ticket(self.ticket(seat_id)?)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum SeatError {
+    EmptyInput,
+    InvalidPass { line: usize, reason: String },
+    InvalidSeatId { line: usize, reason: String },
+    // the layout cannot be used for the requested output
+    Layout(String),
+    // the input could not be read, e.g. because it is not valid UTF-8
+    Io(String),
 }
 
-fn seat_id(ticket: Ticket) -> u32 {
-    let (row, col) = ticket;
-    row as u32 * 8 + col as u32
+impl fmt::Display for SeatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SeatError::EmptyInput => write!(f, "no boarding passes"),
+            SeatError::InvalidPass { line, reason } | SeatError::InvalidSeatId { line, reason } => {
+                write!(f, "line {}: {}", line, reason)
+            }
+            SeatError::Layout(reason) => write!(f, "{}", reason),
+            SeatError::Io(reason) => write!(f, "cannot read input: {}", reason),
+        }
+    }
+}
+
+// parse one boarding pass per line, skipping (and reporting) invalid ones if `lenient`
+fn read_tickets(
+    layout: &SeatLayout,
+    reader: impl BufRead,
+    lenient: bool,
+) -> Result<Vec<Ticket>, SeatError> {
+    let mut tickets = vec![];
+
+    for (ii, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| SeatError::Io(e.to_string()))?;
+
+        match layout.parse_ticket(line.trim_end()) {
+            Ok(ticket) => tickets.push(ticket),
+            Err(reason) => {
+                let err = SeatError::InvalidPass {
+                    line: ii + 1,
+                    reason,
+                };
+
+                if !lenient {
+                    return Err(err);
+                }
+
+                eprintln!("skipping {}", err);
+            }
+        }
+    }
+
+    Ok(tickets)
 }
 
-fn puzzle_1(ids: &[u32]) -> Option<&u32> {
-    ids.iter().max()
+fn puzzle_1(ids: &[u32]) -> Result<u32, SeatError> {
+    ids.iter().copied().max().ok_or(SeatError::EmptyInput)
 }
 
-// assume `ids` is sorted
-fn puzzle_2(ids: &[u32]) -> Option<u32> {
-    let mut prev = ids[0];
+// the first empty seat between the lowest and highest occupied one; `ids` need not be sorted
+fn puzzle_2(ids: &[u32]) -> Result<Option<u32>, SeatError> {
+    let min = ids.iter().copied().min().ok_or(SeatError::EmptyInput)?;
+    let max = puzzle_1(ids)?;
+
+    // `ids` cannot fill all of the first `ids.len() + 1` seats from `min`, so the first empty
+    // seat is among them unless the span of ids is shorter
+    let seats = ((max - min) as usize + 1).min(ids.len() + 1);
+    let mut occupied = vec![false; seats];
 
-    for id in &ids[1..] {
-        if *id != prev + 1 {
-            return Some(prev + 1);
+    for id in ids {
+        if let Some(seat) = occupied.get_mut((id - min) as usize) {
+            *seat = true;
         }
+    }
+
+    Ok(occupied.iter().position(|o| !o).map(|ii| min + ii as u32))
+}
+
+// the boarding pass for each seat id on the input
+fn encode(layout: &SeatLayout, reader: impl BufRead) -> Result<Vec<String>, SeatError> {
+    let mut passes = vec![];
 
-        prev = *id;
+    for (ii, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| SeatError::Io(e.to_string()))?;
+        let err = |reason| SeatError::InvalidSeatId {
+            line: ii + 1,
+            reason,
+        };
+
+        let seat_id = line
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| err(format!("Unexpected seat id: {}", line.trim())))?;
+
+        passes.push(layout.encode_seat_id(seat_id).map_err(err)?);
     }
 
-    None
+    Ok(passes)
 }
 
-fn main() {
+fn describe_rows(rows: &Range<u64>) -> String {
+    match rows {
+        r if r.start == r.end => "none".to_owned(),
+        r => format!("{}-{}", r.start, r.end - 1),
+    }
+}
+
+// print the seat map and the empty and duplicate seats of the manifest
+fn analyze(layout: &SeatLayout, mut manifest: Manifest, tickets: &[Ticket]) {
+    for ticket in tickets {
+        manifest.add(*ticket);
+    }
+
+    print!("{}", manifest.render());
+
+    let (front, back) = manifest.missing_rows();
+    println!("missing front rows = {}", describe_rows(&front));
+    println!("missing back rows = {}", describe_rows(&back));
+
+    for ticket in manifest.interior_gaps() {
+        let pass = layout.encode_ticket(ticket).unwrap();
+        println!("empty seat = {} ({})", layout.seat_id(ticket), pass);
+    }
+
+    for (ticket, count) in manifest.duplicates() {
+        let pass = layout.encode_ticket(ticket).unwrap();
+        println!(
+            "duplicate seat = {} ({}, {} times)",
+            layout.seat_id(ticket),
+            pass,
+            count
+        );
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    encode: bool,
+    manifest: bool,
+    lenient: bool,
+    layout: Vec<String>,
+}
+
+fn parse_args() -> Options {
+    let mut options = Options::default();
+
+    for arg in env::args().skip(1) {
+        match &arg[..] {
+            "--encode" => options.encode = true,
+            "--manifest" => options.manifest = true,
+            "--lenient" => options.lenient = true,
+            _ => options.layout.push(arg),
+        }
+    }
+
+    options
+}
+
+fn parse_layout(args: &[String]) -> Result<SeatLayout, String> {
+    match args {
+        [] => Ok(STANDARD),
+        [rows, cols] => {
+            let err = |_| format!("Unexpected layout: {} {}", rows, cols);
+            SeatLayout::new(rows.parse().map_err(err)?, cols.parse().map_err(err)?)
+        }
+        _ => Err(
+            "Usage: day-05 [--encode | --manifest] [--lenient] [<row bits> <column bits>]"
+                .to_owned(),
+        ),
+    }
+}
+
+fn run(options: &Options, layout: &SeatLayout) -> Result<(), SeatError> {
     let stdin = io::stdin();
 
-    let mut ids: Vec<_> = stdin
-        .lock()
-        .lines()
-        .map(Result::unwrap)
-        .map(|s| parse_ticket(&s[..]))
-        .map(Result::unwrap)
-        .map(seat_id)
-        .collect();
+    if options.encode {
+        for pass in encode(layout, stdin.lock())? {
+            println!("{}", pass);
+        }
+        return Ok(());
+    }
 
-    ids.sort();
+    // check the layout before reading any input
+    let manifest = if options.manifest {
+        Some(Manifest::new(*layout).map_err(SeatError::Layout)?)
+    } else {
+        None
+    };
 
-    println!("puzzle #1 = {:?}", puzzle_1(&ids));
-    println!("puzzle #2 = {:?}", puzzle_2(&ids));
+    let tickets = read_tickets(layout, stdin.lock(), options.lenient)?;
+
+    if let Some(manifest) = manifest {
+        analyze(layout, manifest, &tickets);
+        return Ok(());
+    }
+
+    let ids: Vec<_> = tickets.into_iter().map(|t| layout.seat_id(t)).collect();
+
+    println!("puzzle #1 = {:?}", puzzle_1(&ids)?);
+    println!("puzzle #2 = {:?}", puzzle_2(&ids)?);
+
+    Ok(())
+}
+
+fn main() {
+    let options = parse_args();
+
+    let layout = match parse_layout(&options.layout) {
+        Ok(layout) => layout,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if let Err(e) = run(&options, &layout) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use std::io::Cursor;
+
+    fn chars(raw: &str) -> Vec<char> {
+        raw.chars().collect()
+    }
 
     #[test]
     fn test_parse_binary() {
-        assert_eq!(parse_binary("FFFFFFF"), Ok(0));
-        assert_eq!(parse_binary("FBFBBFF"), Ok(44));
-        assert_eq!(parse_binary("BBFBBFF"), Ok(108));
-        assert_eq!(parse_binary("BBBBBBB"), Ok(127));
+        assert_eq!(parse_binary(&chars("FFFFFFF"), ('F', 'B')), Ok(0));
+        assert_eq!(parse_binary(&chars("FBFBBFF"), ('F', 'B')), Ok(44));
+        assert_eq!(parse_binary(&chars("BBFBBFF"), ('F', 'B')), Ok(108));
+        assert_eq!(parse_binary(&chars("BBBBBBB"), ('F', 'B')), Ok(127));
 
-        assert_eq!(parse_binary("LLL"), Ok(0));
-        assert_eq!(parse_binary("RLR"), Ok(5));
-        assert_eq!(parse_binary("RRR"), Ok(7));
+        assert_eq!(parse_binary(&chars("LLL"), ('L', 'R')), Ok(0));
+        assert_eq!(parse_binary(&chars("RLR"), ('L', 'R')), Ok(5));
+        assert_eq!(parse_binary(&chars("RRR"), ('L', 'R')), Ok(7));
 
-        assert!(parse_binary("FFFFFFFFF").is_err());
-        assert!(parse_binary("LRX").is_err());
+        assert_eq!(
+            parse_binary(&chars(&"1".repeat(32)), ('0', '1')),
+            Ok(u32::MAX)
+        );
+        assert!(parse_binary(&chars(&"1".repeat(33)), ('0', '1')).is_err());
+        assert!(parse_binary(&chars("LRX"), ('L', 'R')).is_err());
+        assert!(parse_binary(&chars("FFB"), ('L', 'R')).is_err());
     }
 
     #[test]
     fn test_parse_ticket() {
-        assert_eq!(parse_ticket("FBFBBFFRLR"), Ok((44, 5)));
-        assert_eq!(parse_ticket("BFFFBBFRRR"), Ok((70, 7)));
-        assert_eq!(parse_ticket("FFFBBBFRRR"), Ok((14, 7)));
-        assert_eq!(parse_ticket("BBFFBBFRLL"), Ok((102, 4)));
+        assert_eq!(STANDARD.parse_ticket("FBFBBFFRLR"), Ok((44, 5)));
+        assert_eq!(STANDARD.parse_ticket("BFFFBBFRRR"), Ok((70, 7)));
+        assert_eq!(STANDARD.parse_ticket("FFFBBBFRRR"), Ok((14, 7)));
+        assert_eq!(STANDARD.parse_ticket("BBFFBBFRLL"), Ok((102, 4)));
 
-        assert!(parse_ticket("BBFFBBRLL").is_err());
-        assert!(parse_ticket("BBFFBBFRLLL").is_err());
+        assert!(STANDARD.parse_ticket("BBFFBBRLL").is_err());
+        assert!(STANDARD.parse_ticket("BBFFBBFRLLL").is_err());
+        assert!(STANDARD.parse_ticket("BBFFBBLRRR").is_err());
+        assert!(STANDARD.parse_ticket("FBFBBFüRL").is_err());
+    }
+
+    #[test]
+    fn test_parse_ticket_layout() {
+        let small = SeatLayout::new(4, 2).unwrap();
+        assert_eq!(small.parse_ticket("BFFBRL"), Ok((9, 2)));
+        assert!(small.parse_ticket("FBFBBFFRLR").is_err());
+
+        let custom = SeatLayout {
+            row_letters: ('0', '1'),
+            col_letters: ('a', 'b'),
+            ..STANDARD
+        };
+        assert_eq!(custom.parse_ticket("0101100bab"), Ok((44, 5)));
+
+        assert!(SeatLayout::new(30, 3).is_err());
+        assert!(SeatLayout::new(u32::MAX, 3).is_err());
+
+        let columns = SeatLayout::new(0, 32).unwrap();
+        assert_eq!(
+            columns.seat_id(columns.parse_ticket(&"R".repeat(32)).unwrap()),
+            u32::MAX
+        );
     }
 
     #[test]
     fn test_seat_id() {
-        assert_eq!(seat_id(parse_ticket("FBFBBFFRLR").unwrap()), 357);
-        assert_eq!(seat_id(parse_ticket("BFFFBBFRRR").unwrap()), 567);
-        assert_eq!(seat_id(parse_ticket("FFFBBBFRRR").unwrap()), 119);
-        assert_eq!(seat_id(parse_ticket("BBFFBBFRLL").unwrap()), 820);
+        let seat_id = |raw| STANDARD.seat_id(STANDARD.parse_ticket(raw).unwrap());
+        assert_eq!(seat_id("FBFBBFFRLR"), 357);
+        assert_eq!(seat_id("BFFFBBFRRR"), 567);
+        assert_eq!(seat_id("FFFBBBFRRR"), 119);
+        assert_eq!(seat_id("BBFFBBFRLL"), 820);
+
+        let wide = SeatLayout::new(7, 4).unwrap();
+        assert_eq!(wide.seat_id((44, 5)), 709);
+    }
+
+    #[test]
+    fn test_read_tickets() {
+        let input = "FBFBBFFRLR\r\nBFFFBBFRRR\nFBFBBFFRL\n\nBBFFBBFRLL\n";
+
+        assert_eq!(
+            read_tickets(&STANDARD, Cursor::new(input), false),
+            Err(SeatError::InvalidPass {
+                line: 3,
+                reason: "Unexpected ticket format: FBFBBFFRL".to_owned()
+            })
+        );
+        assert_eq!(
+            read_tickets(&STANDARD, Cursor::new(input), true),
+            Ok(vec![(44, 5), (70, 7), (102, 4)])
+        );
+        assert_eq!(read_tickets(&STANDARD, Cursor::new(""), false), Ok(vec![]));
+        assert_eq!(
+            read_tickets(&STANDARD, Cursor::new(b"FBFBBFFRLR\n\xff\n"), true),
+            Err(SeatError::Io(
+                "stream did not contain valid UTF-8".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_puzzle_1() {
+        assert_eq!(puzzle_1(&[357, 820, 567]), Ok(820));
+        assert_eq!(puzzle_1(&[]), Err(SeatError::EmptyInput));
+    }
+
+    #[test]
+    fn test_puzzle_2() {
+        assert_eq!(puzzle_2(&[9, 6, 10, 8, 5]), Ok(Some(7)));
+        assert_eq!(puzzle_2(&[12, 10, 13, 9]), Ok(Some(11)));
+        assert_eq!(puzzle_2(&[3, 1, 2]), Ok(None));
+        assert_eq!(puzzle_2(&[7]), Ok(None));
+        assert_eq!(puzzle_2(&[u32::MAX, 0]), Ok(Some(1)));
+        assert_eq!(puzzle_2(&[0, 1, 1, 2, 4]), Ok(Some(3)));
+        assert_eq!(puzzle_2(&[0, 1, 2, 1000]), Ok(Some(3)));
+        assert_eq!(puzzle_2(&[]), Err(SeatError::EmptyInput));
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(STANDARD.encode_ticket((44, 5)), Ok("FBFBBFFRLR".to_owned()));
+        assert_eq!(STANDARD.encode_seat_id(567), Ok("BFFFBBFRRR".to_owned()));
+        assert_eq!(STANDARD.encode_seat_id(0), Ok("FFFFFFFLLL".to_owned()));
+        assert_eq!(STANDARD.encode_seat_id(1023), Ok("BBBBBBBRRR".to_owned()));
+
+        assert!(STANDARD.encode_ticket((128, 0)).is_err());
+        assert!(STANDARD.encode_ticket((0, 8)).is_err());
+        assert!(STANDARD.encode_seat_id(1024).is_err());
+
+        let columns = SeatLayout::new(0, 32).unwrap();
+        assert_eq!(columns.encode_seat_id(u32::MAX), Ok("R".repeat(32)));
+    }
+
+    #[test]
+    fn test_encode_lines() {
+        assert_eq!(
+            encode(&STANDARD, Cursor::new("357\n 820 \r\n")),
+            Ok(vec!["FBFBBFFRLR".to_owned(), "BBFFBBFRLL".to_owned()])
+        );
+        assert_eq!(
+            encode(&STANDARD, Cursor::new("357\n12x\n")),
+            Err(SeatError::InvalidSeatId {
+                line: 2,
+                reason: "Unexpected seat id: 12x".to_owned()
+            })
+        );
+        assert_eq!(
+            encode(&STANDARD, Cursor::new("1024")),
+            Err(SeatError::InvalidSeatId {
+                line: 1,
+                reason: "Unexpected seat id: 1024".to_owned()
+            })
+        );
+    }
+
+    fn layout_and_ticket() -> impl Strategy<Value = (SeatLayout, Ticket)> {
+        (0..=16u32, 0..=16u32).prop_flat_map(|(row_bits, col_bits)| {
+            let layout = SeatLayout::new(row_bits, col_bits).unwrap();
+            (Just(layout), 0..1u32 << row_bits, 0..1u32 << col_bits)
+                .prop_map(|(layout, row, col)| (layout, (row, col)))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_encode_decode(raw in "[FB]{7}[LR]{3}") {
+            let ticket = STANDARD.parse_ticket(&raw).unwrap();
+            prop_assert_eq!(STANDARD.encode_ticket(ticket), Ok(raw.clone()));
+
+            let seat_id = STANDARD.seat_id(ticket);
+            prop_assert_eq!(STANDARD.encode_seat_id(seat_id), Ok(raw));
+        }
+
+        #[test]
+        fn prop_decode_encode((layout, ticket) in layout_and_ticket()) {
+            let raw = layout.encode_ticket(ticket).unwrap();
+            prop_assert_eq!(layout.parse_ticket(&raw), Ok(ticket));
+            prop_assert_eq!(layout.ticket(layout.seat_id(ticket)), Ok(ticket));
+        }
     }
 }
@@ -0,0 +1,169 @@
+use crate::{SeatLayout, Ticket};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::ops::Range;
+
+const EMPTY: char = '.';
+const OCCUPIED: char = '#';
+const DUPLICATE: char = '!';
+
+// the seat map lists every seat, so larger layouts are rejected
+const MAX_SEATS: u64 = 1 << 16;
+
+// all boarding passes of a flight, with the number of times each seat was boarded
+pub struct Manifest {
+    layout: SeatLayout,
+    seats: BTreeMap<Ticket, usize>,
+}
+
+impl Manifest {
+    pub fn new(layout: SeatLayout) -> Result<Manifest, String> {
+        if layout.row_bits + layout.col_bits > MAX_SEATS.trailing_zeros() {
+            return Err(format!(
+                "Unexpected layout: the manifest lists at most {} seats",
+                MAX_SEATS
+            ));
+        }
+
+        Ok(Manifest {
+            layout,
+            seats: BTreeMap::new(),
+        })
+    }
+
+    pub fn add(&mut self, ticket: Ticket) {
+        *self.seats.entry(ticket).or_insert(0) += 1;
+    }
+
+    fn rows(&self) -> Range<u64> {
+        0..1 << self.layout.row_bits
+    }
+
+    fn cols(&self) -> Range<u64> {
+        0..1 << self.layout.col_bits
+    }
+
+    fn seat(&self, row: u64, col: u64) -> char {
+        match self.seats.get(&(row as u32, col as u32)) {
+            None => EMPTY,
+            Some(1) => OCCUPIED,
+            Some(_) => DUPLICATE,
+        }
+    }
+
+    // seats boarded more than once, with the number of boardings
+    pub fn duplicates(&self) -> Vec<(Ticket, usize)> {
+        self.seats
+            .iter()
+            .filter(|(_, n)| **n > 1)
+            .map(|(t, n)| (*t, *n))
+            .collect()
+    }
+
+    // all rows before the first and after the last row with an occupied seat
+    pub fn missing_rows(&self) -> (Range<u64>, Range<u64>) {
+        let rows = self.rows();
+
+        match (self.seats.keys().next(), self.seats.keys().last()) {
+            (Some(first), Some(last)) => (rows.start..first.0 as u64, last.0 as u64 + 1..rows.end),
+            _ => (rows.clone(), rows.end..rows.end),
+        }
+    }
+
+    pub fn empty_seats(&self) -> Vec<Ticket> {
+        let mut empty = vec![];
+
+        for row in self.rows() {
+            for col in self.cols() {
+                if self.seat(row, col) == EMPTY {
+                    empty.push((row as u32, col as u32));
+                }
+            }
+        }
+
+        empty
+    }
+
+    // empty seats that are not in one of the missing front or back rows
+    pub fn interior_gaps(&self) -> Vec<Ticket> {
+        let (front, back) = self.missing_rows();
+
+        self.empty_seats()
+            .into_iter()
+            .filter(|(row, _)| !front.contains(&(*row as u64)) && !back.contains(&(*row as u64)))
+            .collect()
+    }
+
+    pub fn render(&self) -> String {
+        let width = (self.rows().end - 1).to_string().len();
+        let mut map = String::new();
+
+        for row in self.rows() {
+            let seats: String = self.cols().map(|col| self.seat(row, col)).collect();
+            writeln!(map, "{:>width$} {}", row, seats, width = width).unwrap();
+        }
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(tickets: &[Ticket]) -> Manifest {
+        let mut manifest = Manifest::new(SeatLayout::new(3, 2).unwrap()).unwrap();
+
+        for ticket in tickets {
+            manifest.add(*ticket);
+        }
+
+        manifest
+    }
+
+    #[test]
+    fn test_new() {
+        assert!(Manifest::new(SeatLayout::new(10, 6).unwrap()).is_ok());
+        assert!(Manifest::new(SeatLayout::new(0, 24).unwrap()).is_err());
+        assert!(Manifest::new(SeatLayout::new(16, 16).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_analysis() {
+        let manifest = manifest(&[
+            (2, 1),
+            (2, 2),
+            (2, 3),
+            (3, 0),
+            (3, 2),
+            (3, 3),
+            (4, 0),
+            (4, 1),
+            (4, 1),
+            (5, 2),
+        ]);
+
+        assert_eq!(manifest.missing_rows(), (0..2, 6..8));
+        assert_eq!(manifest.duplicates(), vec![((4, 1), 2)]);
+        assert_eq!(
+            manifest.interior_gaps(),
+            vec![(2, 0), (3, 1), (4, 2), (4, 3), (5, 0), (5, 1), (5, 3)]
+        );
+        assert_eq!(manifest.empty_seats().len(), 32 - 9);
+
+        assert_eq!(
+            manifest.render(),
+            "0 ....\n1 ....\n2 .###\n3 #.##\n4 #!..\n5 ..#.\n6 ....\n7 ....\n"
+        );
+    }
+
+    #[test]
+    fn test_analysis_empty() {
+        let manifest = manifest(&[]);
+
+        assert_eq!(manifest.missing_rows(), (0..8, 8..8));
+        assert_eq!(manifest.duplicates(), vec![]);
+        assert_eq!(manifest.interior_gaps(), vec![]);
+        assert_eq!(manifest.empty_seats().len(), 32);
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeSet;
+use std::iter::FromIterator;
+
+// set of answered questions: `a`..`z` are stored as bits of a mask,
+// any other character falls back to a regular set
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnswerSet {
+    bits: u32,
+    other: BTreeSet<char>,
+}
+
+fn bit(ch: char) -> Option<u32> {
+    match ch {
+        'a'..='z' => Some(1 << (ch as u32 - 'a' as u32)),
+        _ => None,
+    }
+}
+
+impl AnswerSet {
+    pub fn new() -> AnswerSet {
+        AnswerSet::default()
+    }
+
+    pub fn insert(&mut self, ch: char) {
+        match bit(ch) {
+            Some(b) => self.bits |= b,
+            None => {
+                self.other.insert(ch);
+            }
+        }
+    }
+
+    pub fn contains(&self, ch: char) -> bool {
+        match bit(ch) {
+            Some(b) => self.bits & b != 0,
+            None => self.other.contains(&ch),
+        }
+    }
+
+    pub fn union(&self, other: &AnswerSet) -> AnswerSet {
+        AnswerSet {
+            bits: self.bits | other.bits,
+            other: self.other.union(&other.other).cloned().collect(),
+        }
+    }
+
+    pub fn intersection(&self, other: &AnswerSet) -> AnswerSet {
+        AnswerSet {
+            bits: self.bits & other.bits,
+            other: self.other.intersection(&other.other).cloned().collect(),
+        }
+    }
+
+    pub fn difference(&self, other: &AnswerSet) -> AnswerSet {
+        AnswerSet {
+            bits: self.bits & !other.bits,
+            other: self.other.difference(&other.other).cloned().collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize + self.other.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0 && self.other.is_empty()
+    }
+
+    // `a`..`z` in order, followed by any other characters
+    pub fn iter(&self) -> impl Iterator<Item = char> + '_ {
+        let bits = self.bits;
+
+        ('a'..='z')
+            .filter(move |ch| bits & bit(*ch).unwrap() != 0)
+            .chain(self.other.iter().cloned())
+    }
+}
+
+impl FromIterator<char> for AnswerSet {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> AnswerSet {
+        let mut set = AnswerSet::new();
+
+        for ch in iter {
+            set.insert(ch);
+        }
+
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(raw: &str) -> AnswerSet {
+        raw.chars().collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut answers = AnswerSet::new();
+        assert_eq!(answers.len(), 0);
+        assert!(answers.is_empty());
+
+        answers.insert('a');
+        answers.insert('z');
+        answers.insert('a');
+        answers.insert('É');
+
+        assert_eq!(answers.len(), 3);
+        assert!(!answers.is_empty());
+        assert_eq!(answers, set("zaÉ"));
+        assert_ne!(answers, set("azA"));
+
+        assert!(answers.contains('a'));
+        assert!(answers.contains('z'));
+        assert!(answers.contains('É'));
+        assert!(!answers.contains('b'));
+        assert!(!answers.contains('A'));
+        assert_eq!(answers.iter().collect::<String>(), "azÉ");
+    }
+
+    #[test]
+    fn test_union() {
+        assert_eq!(set("ab").union(&set("ac")), set("abc"));
+        assert_eq!(set("a1").union(&set("2")), set("a12"));
+        assert_eq!(set("").union(&set("")), set(""));
+    }
+
+    #[test]
+    fn test_intersection() {
+        assert_eq!(set("ab").intersection(&set("ac")), set("a"));
+        assert_eq!(set("a12").intersection(&set("b2")), set("2"));
+        assert_eq!(set("abc").intersection(&set("")), set(""));
+    }
+
+    #[test]
+    fn test_difference() {
+        assert_eq!(set("abc").difference(&set("b")), set("ac"));
+        assert_eq!(set("a12").difference(&set("1a")), set("2"));
+        assert_eq!(set("").difference(&set("a")), set(""));
+    }
+}
@@ -1,60 +1,78 @@
-use std::collections::HashSet;
-use std::io::{self, Read};
-
-type Declaration = Vec<HashSet<char>>;
+mod answers;
+mod query;
+mod report;
+
+use advent_of_code_2020::records;
+use answers::AnswerSet;
+use query::Query;
+use report::Report;
+use std::env;
+use std::io::{self, BufRead};
+
+type Declaration = Vec<AnswerSet>;
 type Problem = Vec<Declaration>;
 
-fn read_declaration(chars: &mut impl Iterator<Item = char>) -> Declaration {
-    let mut declaration = vec![HashSet::new()];
-
-    for ch in chars {
-        if ch == '\n' {
-            if declaration.last().unwrap().is_empty() {
-                break;
+const DEFAULT_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+
+// characters outside of `alphabet` are rejected if `strict`, and dropped otherwise; a line
+// left without answers is skipped (and reported) rather than counted as a person
+fn parse_declaration(
+    lines: &[String],
+    first_line: usize,
+    alphabet: &AnswerSet,
+    strict: bool,
+) -> Result<Declaration, String> {
+    let mut declaration = vec![];
+
+    for (ii, line) in lines.iter().enumerate() {
+        let mut answers = AnswerSet::new();
+
+        for ch in line.chars() {
+            match alphabet.contains(ch) {
+                true => answers.insert(ch),
+                false if strict => {
+                    return Err(format!(
+                        "line {}: unexpected answer {:?}",
+                        first_line + ii,
+                        ch
+                    ))
+                }
+                false => {}
             }
+        }
 
-            declaration.push(HashSet::new());
+        if answers.is_empty() {
+            eprintln!(
+                "skipping line {}: no answers in the alphabet",
+                first_line + ii
+            );
             continue;
         }
 
-        declaration.last_mut().unwrap().insert(ch);
+        declaration.push(answers);
     }
 
-    if declaration.last().unwrap().is_empty() {
-        declaration.pop();
-    }
-
-    declaration
+    Ok(declaration)
 }
 
-fn read_problem(chars: &mut impl Iterator<Item = char>) -> Problem {
-    let mut problem = vec![];
-
-    loop {
-        match read_declaration(chars) {
-            d if d.is_empty() => break,
-            d => problem.push(d),
-        }
-    }
-
-    problem
+fn read_problem(reader: impl BufRead, alphabet: &AnswerSet, strict: bool) -> io::Result<Problem> {
+    records::groups(reader)
+        .map(|group| {
+            let group = group?;
+            parse_declaration(&group.lines, group.first_line, alphabet, strict)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        // a group whose lines were all skipped has nobody left in it
+        .filter(|declaration| !matches!(declaration, Ok(d) if d.is_empty()))
+        .collect()
 }
 
 fn count_anyone(declaration: &Declaration) -> usize {
-    declaration
-        .iter()
-        .fold(HashSet::new(), |acc, d| acc.union(d).cloned().collect())
-        .len()
+    Query::Anyone.count(declaration)
 }
 
 fn count_everyone(declaration: &Declaration) -> usize {
-    declaration
-        .iter()
-        .skip(1)
-        .fold(declaration[0].clone(), |acc, d| {
-            acc.intersection(d).cloned().collect()
-        })
-        .len()
+    Query::Everyone.count(declaration)
 }
 
 fn puzzle_1(problem: &Problem) -> usize {
@@ -65,14 +83,65 @@ fn puzzle_2(problem: &Problem) -> usize {
     problem.iter().map(count_everyone).sum()
 }
 
+fn print_histogram(problem: &Problem) {
+    let histogram = query::histogram(problem);
+    let max = histogram.values().copied().max().unwrap_or(0);
+
+    for (ch, n) in histogram {
+        // scale bars to at most 50 characters
+        let bar = "#".repeat((n * 50).div_ceil(max));
+        println!("{} {:>6} {}", ch, n, bar);
+    }
+}
+
+// with no arguments, solve the puzzle; otherwise print the sum of each query over all groups,
+// the per-question histogram for `histogram`, or the statistics of each group for `report`
+// (`report:csv` and `report:json` for machine-readable output)
+//
+// answers are restricted to `--alphabet <chars>` (`a`..`z` by default); other characters are
+// dropped, or rejected with `--strict`
 fn main() -> io::Result<()> {
-    let mut buffer = String::new();
-    io::stdin().read_to_string(&mut buffer)?;
-    let mut iter = buffer.chars();
-    let problem = read_problem(&mut iter);
+    let mut alphabet = DEFAULT_ALPHABET.to_owned();
+    let mut strict = false;
+    let mut args = vec![];
+    let mut raw_args = env::args().skip(1);
+
+    while let Some(arg) = raw_args.next() {
+        match &arg[..] {
+            "--strict" => strict = true,
+            "--alphabet" => {
+                alphabet = raw_args.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "Missing alphabet")
+                })?
+            }
+            _ => args.push(arg),
+        }
+    }
 
-    println!("puzzle #1 = {:?}", puzzle_1(&problem));
-    println!("puzzle #2 = {:?}", puzzle_2(&problem));
+    let stdin = io::stdin();
+    let problem = read_problem(stdin.lock(), &alphabet.chars().collect(), strict)?;
+
+    if args.is_empty() {
+        println!("puzzle #1 = {:?}", puzzle_1(&problem));
+        println!("puzzle #2 = {:?}", puzzle_2(&problem));
+    }
+
+    for arg in args {
+        match &arg[..] {
+            "histogram" => print_histogram(&problem),
+            "report" => print!("{}", Report::new(&problem)),
+            "report:csv" => print!("{}", Report::new(&problem).to_csv()),
+            "report:json" => println!("{}", Report::new(&problem).to_json()),
+            _ => {
+                let query: Query = arg
+                    .parse()
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+                let sum: usize = problem.iter().map(|d| query.count(d)).sum();
+                println!("{} = {}", arg, sum);
+            }
+        }
+    }
 
     Ok(())
 }
@@ -80,50 +149,98 @@ fn main() -> io::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use maplit::hashset as set;
+    use proptest::prelude::*;
+    use std::io::Cursor;
+
+    fn set(raw: &str) -> AnswerSet {
+        raw.chars().collect()
+    }
+
+    fn read(input: &str) -> Problem {
+        let alphabet = DEFAULT_ALPHABET.chars().collect();
+        read_problem(Cursor::new(input), &alphabet, false).unwrap()
+    }
 
     #[test]
-    fn test_read_declaration() {
+    fn test_read_problem() {
         let input = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb";
-        let mut iter = input.chars();
 
-        assert_eq!(read_declaration(&mut iter), vec![set! {'a', 'b', 'c'}]);
-        assert_eq!(
-            read_declaration(&mut iter),
-            vec![set! {'a'}, set! {'b'}, set! {'c'}]
-        );
         assert_eq!(
-            read_declaration(&mut iter),
-            vec![set! {'a', 'b'}, set! {'a', 'c'}]
+            read(input),
+            vec![
+                vec![set("abc")],
+                vec![set("a"), set("b"), set("c")],
+                vec![set("ab"), set("ac")],
+                vec![set("a"), set("a"), set("a"), set("a")],
+                vec![set("b")],
+            ]
         );
+    }
+
+    #[test]
+    fn test_read_problem_crlf() {
+        let input = "\r\nab\r\nac\r\n\r\n\r\nb \r\n";
+
         assert_eq!(
-            read_declaration(&mut iter),
-            vec![set! {'a'}, set! {'a'}, set! {'a'}, set! {'a'}]
+            read(input),
+            vec![vec![set("ab"), set("ac")], vec![set("b")]]
         );
-        assert_eq!(read_declaration(&mut iter), vec![set! {'b'}]);
+    }
+
+    #[test]
+    fn test_read_problem_alphabet() {
+        let input = "a\rb c\nA1\n\nxyz";
+
+        assert_eq!(read(input), vec![vec![set("abc")], vec![set("xyz")]]);
+
+        // a line without answers in the alphabet is not a person answering nothing
+        let problem = read("ab\nAB\nb\n\n12\n\nc");
+        assert_eq!(problem, vec![vec![set("ab"), set("b")], vec![set("c")]]);
+        assert_eq!(puzzle_2(&problem), 2);
+
+        let alphabet = "abcxyz1".chars().collect();
+        let err = read_problem(Cursor::new(input), &alphabet, true).unwrap_err();
+        assert_eq!(err.to_string(), "line 1: unexpected answer '\\r'");
+
+        let problem = read_problem(Cursor::new("ab\n1c\n\nx"), &alphabet, true).unwrap();
+        assert_eq!(problem, vec![vec![set("ab"), set("1c")], vec![set("x")]]);
     }
 
     #[test]
     fn test_count_anyone() {
-        assert_eq!(count_anyone(&vec![set! {'a', 'b', 'c'}]), 3);
-        assert_eq!(count_anyone(&vec![set! {'a'}, set! {'b'}, set! {'c'}]), 3);
-        assert_eq!(count_anyone(&vec![set! {'a', 'b'}, set! {'a', 'c'}]), 3);
+        assert_eq!(count_anyone(&vec![set("abc")]), 3);
+        assert_eq!(count_anyone(&vec![set("a"), set("b"), set("c")]), 3);
+        assert_eq!(count_anyone(&vec![set("ab"), set("ac")]), 3);
         assert_eq!(
-            count_anyone(&vec![set! {'a'}, set! {'a'}, set! {'a'}, set! {'a'}]),
+            count_anyone(&vec![set("a"), set("a"), set("a"), set("a")]),
             1
         );
-        assert_eq!(count_anyone(&vec![set! {'b'}]), 1);
+        assert_eq!(count_anyone(&vec![set("b")]), 1);
     }
 
     #[test]
     fn test_count_everyone() {
-        assert_eq!(count_everyone(&vec![set! {'a', 'b', 'c'}]), 3);
-        assert_eq!(count_everyone(&vec![set! {'a'}, set! {'b'}, set! {'c'}]), 0);
-        assert_eq!(count_everyone(&vec![set! {'a', 'b'}, set! {'a', 'c'}]), 1);
+        assert_eq!(count_everyone(&vec![set("abc")]), 3);
+        assert_eq!(count_everyone(&vec![set("a"), set("b"), set("c")]), 0);
+        assert_eq!(count_everyone(&vec![set("ab"), set("ac")]), 1);
         assert_eq!(
-            count_everyone(&vec![set! {'a'}, set! {'a'}, set! {'a'}, set! {'a'}]),
+            count_everyone(&vec![set("a"), set("a"), set("a"), set("a")]),
             1
         );
-        assert_eq!(count_everyone(&vec![set! {'b'}]), 1);
+        assert_eq!(count_everyone(&vec![set("b")]), 1);
+        assert_eq!(count_everyone(&vec![]), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_read_problem_total(input in "[a-c \\t\\r\\n1É]{0,40}") {
+            let problem = read(&input);
+            prop_assert!(puzzle_2(&problem) <= puzzle_1(&problem));
+
+            for declaration in &problem {
+                prop_assert!(!declaration.is_empty());
+                prop_assert!(count_anyone(declaration) <= 3);
+            }
+        }
     }
 }
@@ -0,0 +1,182 @@
+use crate::answers::AnswerSet;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    // questions answered by anyone in the group
+    Anyone,
+    // questions answered by everyone in the group
+    Everyone,
+    // questions answered by at least this many people in the group
+    AtLeast(usize),
+    // questions answered by exactly one person in the group
+    ExactlyOne,
+    // questions of the alphabet answered by nobody in the group
+    Nobody(AnswerSet),
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Query, String> {
+        let err = || format!("Unexpected query: {}", raw);
+        let mut parts = raw.splitn(2, ':');
+
+        match (parts.next(), parts.next()) {
+            (Some("anyone"), None) => Ok(Query::Anyone),
+            (Some("everyone"), None) => Ok(Query::Everyone),
+            (Some("exactly-one"), None) => Ok(Query::ExactlyOne),
+            (Some("at-least"), Some(k)) => match k.parse() {
+                Ok(k) if k > 0 => Ok(Query::AtLeast(k)),
+                _ => Err(err()),
+            },
+            (Some("nobody"), Some(alphabet)) => Ok(Query::Nobody(alphabet.chars().collect())),
+            _ => Err(err()),
+        }
+    }
+}
+
+// number of people in the group answering each question
+pub fn frequencies(declaration: &[AnswerSet]) -> BTreeMap<char, usize> {
+    let mut frequencies = BTreeMap::new();
+
+    for answers in declaration {
+        for ch in answers.iter() {
+            *frequencies.entry(ch).or_insert(0) += 1;
+        }
+    }
+
+    frequencies
+}
+
+// number of people answering each question, across all groups
+pub fn histogram(problem: &[Vec<AnswerSet>]) -> BTreeMap<char, usize> {
+    let mut histogram = BTreeMap::new();
+
+    for declaration in problem {
+        for (ch, n) in frequencies(declaration) {
+            *histogram.entry(ch).or_insert(0) += n;
+        }
+    }
+
+    histogram
+}
+
+impl Query {
+    pub fn answers(&self, declaration: &[AnswerSet]) -> AnswerSet {
+        let answered_by = |pred: &dyn Fn(usize) -> bool| {
+            frequencies(declaration)
+                .into_iter()
+                .filter(|(_, n)| pred(*n))
+                .map(|(ch, _)| ch)
+                .collect()
+        };
+
+        match self {
+            Query::Anyone => declaration
+                .iter()
+                .fold(AnswerSet::new(), |acc, d| acc.union(d)),
+            Query::Everyone => match declaration.split_first() {
+                None => AnswerSet::new(),
+                Some((first, rest)) => rest
+                    .iter()
+                    .fold(first.clone(), |acc, d| acc.intersection(d)),
+            },
+            Query::AtLeast(k) => answered_by(&|n| n >= *k),
+            Query::ExactlyOne => answered_by(&|n| n == 1),
+            Query::Nobody(alphabet) => alphabet.difference(&Query::Anyone.answers(declaration)),
+        }
+    }
+
+    pub fn count(&self, declaration: &[AnswerSet]) -> usize {
+        self.answers(declaration).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn declaration(raw: &[&str]) -> Vec<AnswerSet> {
+        raw.iter().map(|r| r.chars().collect()).collect()
+    }
+
+    fn answers(query: &str, raw: &[&str]) -> String {
+        let query: Query = query.parse().unwrap();
+        query.answers(&declaration(raw)).iter().collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("anyone".parse(), Ok(Query::Anyone));
+        assert_eq!("everyone".parse(), Ok(Query::Everyone));
+        assert_eq!("exactly-one".parse(), Ok(Query::ExactlyOne));
+        assert_eq!("at-least:2".parse(), Ok(Query::AtLeast(2)));
+        assert_eq!(
+            "nobody:abc".parse(),
+            Ok(Query::Nobody("abc".chars().collect()))
+        );
+
+        assert!("at-least:0".parse::<Query>().is_err());
+        assert!("at-least:x".parse::<Query>().is_err());
+        assert!("at-least".parse::<Query>().is_err());
+        assert!("anyone:1".parse::<Query>().is_err());
+        assert!("someone".parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn test_answers() {
+        let group = ["abc", "ab", "bd", "b"];
+
+        assert_eq!(answers("anyone", &group), "abcd");
+        assert_eq!(answers("everyone", &group), "b");
+        assert_eq!(answers("at-least:2", &group), "ab");
+        assert_eq!(answers("at-least:5", &group), "");
+        assert_eq!(answers("exactly-one", &group), "cd");
+        assert_eq!(answers("nobody:abcdefg", &group), "efg");
+        assert_eq!(answers("nobody:xyz", &group), "xyz");
+    }
+
+    #[test]
+    fn test_answers_empty_group() {
+        for query in &["anyone", "everyone", "at-least:1", "exactly-one"] {
+            assert_eq!(answers(query, &[]), "");
+        }
+
+        assert_eq!(answers("nobody:ab", &[]), "ab");
+    }
+
+    #[test]
+    fn test_histogram() {
+        let problem = vec![declaration(&["abc", "ab"]), declaration(&["b", "bz"])];
+
+        let expected: BTreeMap<_, _> = vec![('a', 2), ('b', 4), ('c', 1), ('z', 1)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(histogram(&problem), expected);
+        assert_eq!(
+            frequencies(&problem[1]),
+            vec![('b', 2), ('z', 1)].into_iter().collect()
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_answers_total(raw in prop::collection::vec(".{0,8}", 0..6), k in 1..8usize) {
+            let declaration: Vec<_> = raw.iter().map(|r| r.chars().collect()).collect();
+
+            let anyone = Query::Anyone.answers(&declaration);
+            let everyone = Query::Everyone.answers(&declaration);
+            let at_least = Query::AtLeast(k).answers(&declaration);
+            let exactly_one = Query::ExactlyOne.answers(&declaration);
+
+            prop_assert_eq!(anyone.union(&everyone), anyone.clone());
+            prop_assert_eq!(anyone.union(&at_least), anyone.clone());
+            prop_assert_eq!(anyone.union(&exactly_one), anyone.clone());
+            prop_assert_eq!(Query::Nobody(anyone.clone()).count(&declaration), 0);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::answers::AnswerSet;
+use crate::query::{self, Query};
+use advent_of_code_2020::csv;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct GroupStats {
+    pub group: usize,
+    pub size: usize,
+    pub anyone: String,
+    pub everyone: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Report {
+    pub groups: Vec<GroupStats>,
+    // number of people answering each question, across all groups
+    pub questions: BTreeMap<char, usize>,
+    pub most_common: Vec<char>,
+    pub least_common: Vec<char>,
+    // number of groups of each size
+    pub group_sizes: BTreeMap<usize, usize>,
+}
+
+// questions whose count equals the one selected by `pick`
+fn questions_with(
+    histogram: &BTreeMap<char, usize>,
+    pick: impl Fn(&mut dyn Iterator<Item = usize>) -> Option<usize>,
+) -> Vec<char> {
+    match pick(&mut histogram.values().copied()) {
+        None => vec![],
+        Some(n) => histogram
+            .iter()
+            .filter(|(_, m)| **m == n)
+            .map(|(ch, _)| *ch)
+            .collect(),
+    }
+}
+
+impl Report {
+    pub fn new(problem: &[Vec<AnswerSet>]) -> Report {
+        let groups = problem
+            .iter()
+            .enumerate()
+            .map(|(ii, declaration)| GroupStats {
+                group: ii + 1,
+                size: declaration.len(),
+                anyone: Query::Anyone.answers(declaration).iter().collect(),
+                everyone: Query::Everyone.answers(declaration).iter().collect(),
+            })
+            .collect();
+
+        let questions = query::histogram(problem);
+        let most_common = questions_with(&questions, |counts| counts.max());
+        let least_common = questions_with(&questions, |counts| counts.min());
+
+        let mut group_sizes = BTreeMap::new();
+
+        for declaration in problem {
+            *group_sizes.entry(declaration.len()).or_insert(0) += 1;
+        }
+
+        Report {
+            groups,
+            questions,
+            most_common,
+            least_common,
+            group_sizes,
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = "group,size,anyone,everyone\n".to_owned();
+
+        for g in &self.groups {
+            csv += &format!(
+                "{},{},{},{}\n",
+                g.group,
+                g.size,
+                csv::field(&g.anyone),
+                csv::field(&g.everyone)
+            );
+        }
+
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report is serializable")
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for g in &self.groups {
+            writeln!(
+                f,
+                "group #{}: size = {}, anyone = {:?}, everyone = {:?}",
+                g.group, g.size, g.anyone, g.everyone
+            )?;
+        }
+
+        let join = |chars: &[char]| {
+            chars
+                .iter()
+                .map(|ch| ch.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        writeln!(f, "most common = {}", join(&self.most_common))?;
+        writeln!(f, "least common = {}", join(&self.least_common))?;
+
+        for (size, n) in &self.group_sizes {
+            writeln!(f, "groups of size {} = {}", size, n)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> Report {
+        let problem: Vec<Vec<AnswerSet>> = vec![vec!["abc"], vec!["ab", "ac"], vec!["b", "b"]]
+            .into_iter()
+            .map(|d| d.into_iter().map(|r| r.chars().collect()).collect())
+            .collect();
+
+        Report::new(&problem)
+    }
+
+    #[test]
+    fn test_report() {
+        let report = report();
+
+        assert_eq!(
+            report.groups[1],
+            GroupStats {
+                group: 2,
+                size: 2,
+                anyone: "abc".to_owned(),
+                everyone: "a".to_owned(),
+            }
+        );
+        assert_eq!(report.most_common, vec!['b']);
+        assert_eq!(report.least_common, vec!['c']);
+        assert_eq!(
+            report.group_sizes,
+            vec![(1, 1), (2, 2)].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        let report = report();
+
+        assert_eq!(
+            report.to_csv(),
+            "group,size,anyone,everyone\n1,1,abc,abc\n2,2,abc,a\n3,2,b,b\n"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["groups"][2]["everyone"], "b");
+        assert_eq!(json["questions"]["b"], 4);
+        assert_eq!(json["most_common"][0], "b");
+        assert_eq!(json["group_sizes"]["2"], 2);
+    }
+}
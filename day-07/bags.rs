@@ -0,0 +1,304 @@
+use crate::graph::Graph;
+use std::collections::HashMap;
+use std::fmt;
+
+// index of a bag in the name table of `Bags`; ids follow the alphabetical order of names
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BagId(usize);
+
+// `Graph` with interned bag names, so lookups and traversals index into vectors
+// instead of hashing strings
+pub struct Bags {
+    names: Vec<String>,
+    ids: HashMap<String, BagId>,
+    // bags (and their quantities) directly inside each bag
+    inner: Vec<Vec<(BagId, u32)>>,
+    // bags directly containing each bag
+    outer: Vec<Vec<BagId>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CountError {
+    // the number of bags inside this bag does not fit into a `u64`
+    Overflow(String),
+    // this bag (eventually) contains itself
+    Cycle(String),
+}
+
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CountError::Overflow(bag) => write!(f, "too many bags inside {}", bag),
+            CountError::Cycle(bag) => write!(f, "{} bags contain themselves", bag),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Count {
+    Unvisited,
+    // the bag's contents are being counted
+    Pending,
+    Done(u64),
+}
+
+impl BagId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl Bags {
+    pub fn new(graph: &Graph) -> Bags {
+        let mut names: Vec<_> = graph
+            .iter()
+            .flat_map(|(outer, bags)| Some(outer).into_iter().chain(bags.iter().map(|(b, _)| b)))
+            .cloned()
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+
+        let ids: HashMap<_, _> = names
+            .iter()
+            .enumerate()
+            .map(|(ii, name)| (name.clone(), BagId(ii)))
+            .collect();
+
+        let mut inner_bags = vec![vec![]; names.len()];
+        let mut outer_bags = vec![vec![]; names.len()];
+
+        for (outer, bags) in graph {
+            let outer = ids[outer];
+
+            for (inner, n) in bags {
+                let inner = ids[inner];
+                inner_bags[outer.0].push((inner, *n));
+                outer_bags[inner.0].push(outer);
+            }
+        }
+
+        Bags {
+            names,
+            ids,
+            inner: inner_bags,
+            outer: outer_bags,
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<BagId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: BagId) -> &str {
+        &self.names[id.0]
+    }
+
+    // all bags, in id order
+    pub fn ids(&self) -> impl Iterator<Item = BagId> {
+        (0..self.names.len()).map(BagId)
+    }
+
+    // bags directly inside `bag`, with their quantities
+    pub fn inner(&self, bag: BagId) -> &[(BagId, u32)] {
+        &self.inner[bag.0]
+    }
+
+    // all bags reached from `bag` by repeatedly following `edges`, in id order
+    fn reachable<'b, I>(&'b self, bag: BagId, edges: impl Fn(BagId) -> I) -> Vec<BagId>
+    where
+        I: Iterator<Item = BagId> + 'b,
+    {
+        let mut found = vec![false; self.names.len()];
+        let mut stack = vec![bag];
+
+        while let Some(current) = stack.pop() {
+            for next in edges(current) {
+                if !found[next.0] {
+                    found[next.0] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        self.ids().filter(|id| found[id.0]).collect()
+    }
+
+    // all bags that can eventually contain `bag`, in id order
+    pub fn containers(&self, bag: BagId) -> Vec<BagId> {
+        self.reachable(bag, |b| self.outer[b.0].iter().copied())
+    }
+
+    // all bags `bag` eventually contains, in id order
+    pub fn contents(&self, bag: BagId) -> Vec<BagId> {
+        self.reachable(bag, |b| self.inner[b.0].iter().map(|(inner, _)| *inner))
+    }
+
+    // number of bags `from` amounts to, including itself; each bag is counted once, bottom-up,
+    // using an explicit stack so deep graphs do not overflow the call stack
+    pub fn count_bags(&self, from: BagId) -> Result<u64, CountError> {
+        let mut counts = vec![Count::Unvisited; self.names.len()];
+        let mut stack = vec![(from, false)];
+
+        while let Some((bag, expanded)) = stack.pop() {
+            let bags = &self.inner[bag.0];
+
+            if expanded {
+                let mut total: u64 = 1;
+
+                for (inner, n) in bags {
+                    let count = match counts[inner.0] {
+                        Count::Done(count) => count,
+                        _ => unreachable!("inner bags are counted first"),
+                    };
+
+                    total = count
+                        .checked_mul(*n as u64)
+                        .and_then(|c| c.checked_add(total))
+                        .ok_or_else(|| CountError::Overflow(self.name(bag).to_owned()))?;
+                }
+
+                counts[bag.0] = Count::Done(total);
+                continue;
+            }
+
+            match counts[bag.0] {
+                Count::Done(_) => continue,
+                Count::Pending => return Err(CountError::Cycle(self.name(bag).to_owned())),
+                Count::Unvisited => {}
+            }
+
+            counts[bag.0] = Count::Pending;
+            stack.push((bag, true));
+
+            for (inner, _) in bags {
+                stack.push((*inner, false));
+            }
+        }
+
+        match counts[from.0] {
+            Count::Done(count) => Ok(count),
+            _ => unreachable!("all bags are counted"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::graph;
+
+    fn bags(rules: &[(&str, &[(&str, u32)])]) -> Bags {
+        Bags::new(&graph::graph(rules))
+    }
+
+    fn chain(len: usize, n: u32) -> Bags {
+        Bags::new(&graph::chain(len, n))
+    }
+
+    fn count(bags: &Bags, name: &str) -> Result<u64, CountError> {
+        bags.count_bags(bags.id(name).unwrap())
+    }
+
+    #[test]
+    fn test_names() {
+        let bags = bags(&[("light red", &[("shiny gold", 2)]), ("dark olive", &[])]);
+
+        let names: Vec<_> = ["dark olive", "light red", "shiny gold"]
+            .iter()
+            .map(|name| bags.id(name).map(|id| bags.name(id)))
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![Some("dark olive"), Some("light red"), Some("shiny gold")]
+        );
+        assert_eq!(bags.id("faded blue"), None);
+    }
+
+    #[test]
+    fn test_containers() {
+        let bags = bags(&[
+            ("light red", &[("bright white", 1), ("muted yellow", 2)]),
+            ("dark orange", &[("bright white", 3), ("muted yellow", 4)]),
+            ("bright white", &[("shiny gold", 1)]),
+            ("muted yellow", &[("shiny gold", 2), ("faded blue", 9)]),
+            ("shiny gold", &[("dark olive", 1)]),
+        ]);
+
+        let containers = |name| -> Vec<_> {
+            let ids = bags.containers(bags.id(name).unwrap());
+            ids.into_iter().map(|id| bags.name(id)).collect()
+        };
+
+        assert_eq!(
+            containers("shiny gold"),
+            vec!["bright white", "dark orange", "light red", "muted yellow"]
+        );
+        assert_eq!(
+            containers("faded blue"),
+            vec!["dark orange", "light red", "muted yellow"]
+        );
+        assert_eq!(containers("light red"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_contents() {
+        let bags = bags(&[
+            ("light red", &[("bright white", 1), ("muted yellow", 2)]),
+            ("bright white", &[("shiny gold", 1)]),
+            ("muted yellow", &[("shiny gold", 2), ("faded blue", 9)]),
+        ]);
+
+        let contents = |name| -> Vec<_> {
+            let ids = bags.contents(bags.id(name).unwrap());
+            ids.into_iter().map(|id| bags.name(id)).collect()
+        };
+
+        assert_eq!(contents("muted yellow"), vec!["faded blue", "shiny gold"]);
+        assert_eq!(contents("shiny gold"), Vec::<&str>::new());
+        assert_eq!(contents("light red").len(), 4);
+    }
+
+    #[test]
+    fn test_containers_cycle() {
+        let bags = bags(&[("a", &[("b", 1)]), ("b", &[("a", 1)])]);
+        let a = bags.id("a").unwrap();
+
+        assert_eq!(bags.containers(a), vec![a, bags.id("b").unwrap()]);
+    }
+
+    #[test]
+    fn test_count_bags() {
+        let bags = bags(&[
+            ("shiny gold", &[("dark olive", 1), ("vibrant plum", 2)]),
+            ("dark olive", &[("faded blue", 3), ("dotted black", 4)]),
+            ("vibrant plum", &[("faded blue", 5), ("dotted black", 6)]),
+            ("faded blue", &[]),
+        ]);
+
+        assert_eq!(count(&bags, "shiny gold"), Ok(33));
+        assert_eq!(count(&bags, "dark olive"), Ok(8));
+        assert_eq!(count(&bags, "dotted black"), Ok(1));
+    }
+
+    #[test]
+    fn test_count_bags_deep() {
+        assert_eq!(count(&chain(100_000, 1), "0"), Ok(100_000));
+    }
+
+    #[test]
+    fn test_count_bags_overflow() {
+        // each bag contains two copies of the next one, so bag `0` amounts to 2^70 - 1 bags
+        let bags = chain(70, 2);
+
+        assert_eq!(count(&bags, "10"), Ok((1 << 60) - 1));
+        assert_eq!(count(&bags, "0"), Err(CountError::Overflow("5".to_owned())));
+    }
+
+    #[test]
+    fn test_count_bags_cycle() {
+        let bags = bags(&[("a", &[("b", 1)]), ("b", &[("c", 1)]), ("c", &[("a", 1)])]);
+        assert_eq!(count(&bags, "a"), Err(CountError::Cycle("a".to_owned())));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::bags::{BagId, Bags};
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Dot,
+    Mermaid,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Format, String> {
+        match raw {
+            "dot" => Ok(Format::Dot),
+            "mermaid" => Ok(Format::Mermaid),
+            _ => Err(format!("Unexpected export format: {}", raw)),
+        }
+    }
+}
+
+// part of the graph around a single bag
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scope {
+    // the bag and all bags it eventually contains
+    From(String),
+    // the bag and all bags that can eventually contain it
+    To(String),
+}
+
+// the bags in `scope`, or all bags, in name order
+fn scoped(bags: &Bags, scope: Option<&Scope>) -> Vec<BagId> {
+    let (bag, mut scoped) = match scope {
+        None => return bags.ids().collect(),
+        Some(Scope::From(name)) => match bags.id(name) {
+            Some(bag) => (bag, bags.contents(bag)),
+            None => return vec![],
+        },
+        Some(Scope::To(name)) => match bags.id(name) {
+            Some(bag) => (bag, bags.containers(bag)),
+            None => return vec![],
+        },
+    };
+
+    if let Err(ii) = scoped.binary_search(&bag) {
+        scoped.insert(ii, bag);
+    }
+
+    scoped
+}
+
+// quoted DOT identifier for a bag name
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// renders the bags in `scope` (or all bags) with the number of inner bags as edge labels,
+// filling in the `highlight` bag
+pub fn render(bags: &Bags, format: Format, scope: Option<&Scope>, highlight: &str) -> String {
+    let scoped = scoped(bags, scope);
+    let index = |bag: BagId| scoped.binary_search(&bag).ok();
+    let highlight = bags.id(highlight);
+    let mut out = String::new();
+
+    match format {
+        Format::Dot => writeln!(out, "digraph bags {{").unwrap(),
+        Format::Mermaid => writeln!(out, "graph TD").unwrap(),
+    }
+
+    for (ii, bag) in scoped.iter().enumerate() {
+        let name = bags.name(*bag);
+
+        match format {
+            Format::Dot if Some(*bag) == highlight => {
+                writeln!(out, "    {} [style=filled, fillcolor=gold];", dot_id(name))
+            }
+            Format::Dot => writeln!(out, "    {};", dot_id(name)),
+            Format::Mermaid => writeln!(out, "    n{}[\"{}\"]", ii, name.replace('"', "#quot;")),
+        }
+        .unwrap();
+    }
+
+    for (ii, bag) in scoped.iter().enumerate() {
+        for (inner, n) in bags.inner(*bag) {
+            let jj = match index(*inner) {
+                Some(jj) => jj,
+                None => continue,
+            };
+
+            match format {
+                Format::Dot => writeln!(
+                    out,
+                    "    {} -> {} [label=\"{}\"];",
+                    dot_id(bags.name(*bag)),
+                    dot_id(bags.name(*inner)),
+                    n
+                ),
+                Format::Mermaid => writeln!(out, "    n{} -->|{}| n{}", ii, n, jj),
+            }
+            .unwrap();
+        }
+    }
+
+    match format {
+        Format::Dot => writeln!(out, "}}").unwrap(),
+        Format::Mermaid => {
+            if let Some(ii) = highlight.and_then(index) {
+                writeln!(out, "    style n{} fill:gold", ii).unwrap();
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph;
+
+    fn bags() -> Bags {
+        let rules: &[(&str, &[(&str, u32)])] = &[
+            ("light red", &[("shiny gold", 1), ("muted yellow", 2)]),
+            ("muted yellow", &[("shiny gold", 2), ("faded blue", 9)]),
+            ("shiny gold", &[("faded blue", 1)]),
+            ("faded blue", &[]),
+            ("dotted black", &[]),
+        ];
+
+        Bags::new(&graph::graph(rules))
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let scope = Scope::From("muted yellow".to_owned());
+
+        assert_eq!(
+            render(&bags(), Format::Dot, Some(&scope), "shiny gold"),
+            concat!(
+                "digraph bags {\n",
+                "    \"faded blue\";\n",
+                "    \"muted yellow\";\n",
+                "    \"shiny gold\" [style=filled, fillcolor=gold];\n",
+                "    \"muted yellow\" -> \"shiny gold\" [label=\"2\"];\n",
+                "    \"muted yellow\" -> \"faded blue\" [label=\"9\"];\n",
+                "    \"shiny gold\" -> \"faded blue\" [label=\"1\"];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        let scope = Scope::To("shiny gold".to_owned());
+
+        assert_eq!(
+            render(&bags(), Format::Mermaid, Some(&scope), "shiny gold"),
+            concat!(
+                "graph TD\n",
+                "    n0[\"light red\"]\n",
+                "    n1[\"muted yellow\"]\n",
+                "    n2[\"shiny gold\"]\n",
+                "    n0 -->|1| n2\n",
+                "    n0 -->|2| n1\n",
+                "    n1 -->|2| n2\n",
+                "    style n2 fill:gold\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_all() {
+        let dot = render(&bags(), Format::Dot, None, "unknown");
+
+        assert_eq!(dot.lines().count(), 2 + 5 + 5);
+        assert!(dot.contains("    \"dotted black\";\n"));
+        assert!(!dot.contains("fillcolor"));
+    }
+
+    #[test]
+    fn test_render_dot_escapes() {
+        let bags = Bags::new(&graph::graph(&[("a\\ \"b", &[("c\\", 1)])]));
+
+        assert_eq!(
+            render(&bags, Format::Dot, None, "c\\"),
+            concat!(
+                "digraph bags {\n",
+                "    \"a\\\\ \\\"b\";\n",
+                "    \"c\\\\\" [style=filled, fillcolor=gold];\n",
+                "    \"a\\\\ \\\"b\" -> \"c\\\\\" [label=\"1\"];\n",
+                "}\n",
+            )
+        );
+    }
+}
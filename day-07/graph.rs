@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+// maps each bag to the bags (and their quantities) it directly contains
+pub type Graph = HashMap<String, Vec<(String, u32)>>;
+
+// a single `<outer> bags contain <inner>` line of the input
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub line: usize,
+    pub outer: String,
+    pub inner: Vec<(String, u32)>,
+}
+
+// rules for `(outer, [(inner, n)])` pairs, one per line
+#[cfg(test)]
+pub fn rules(rules: &[(&str, &[(&str, u32)])]) -> Vec<Rule> {
+    rules
+        .iter()
+        .enumerate()
+        .map(|(ii, (outer, inner))| Rule {
+            line: ii + 1,
+            outer: outer.to_string(),
+            inner: inner.iter().map(|(b, n)| (b.to_string(), *n)).collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub fn graph(rules: &[(&str, &[(&str, u32)])]) -> Graph {
+    self::rules(rules)
+        .into_iter()
+        .map(|rule| (rule.outer, rule.inner))
+        .collect()
+}
+
+// bags `0` to `len - 1`, each containing `n` copies of the next one
+#[cfg(test)]
+pub fn chain(len: usize, n: u32) -> Graph {
+    (1..len)
+        .map(|ii| ((ii - 1).to_string(), vec![(ii.to_string(), n)]))
+        .collect()
+}
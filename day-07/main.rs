@@ -1,86 +1,214 @@
-use regex::Regex;
-use std::collections::{HashMap, HashSet, VecDeque};
+mod bags;
+mod export;
+mod graph;
+mod parse;
+mod query;
+mod validate;
+
+use bags::{Bags, CountError};
+use export::{Format, Scope};
+use graph::{Graph, Rule};
+use std::env;
 use std::io::{self, BufRead};
+use std::process;
 
-type Graph = HashMap<String, Vec<(String, u32)>>;
 const TARGET: &str = "shiny gold";
 
-fn read_graph() -> io::Result<Graph> {
-    let outer_re = Regex::new(r"^(.*) bags contain ").unwrap();
-    let inner_re = Regex::new(r"(\d+) (.*?) bag").unwrap();
+enum Command {
+    Contains(String),
+    Containers(String),
+    Path(String, String),
+    Rules,
+    Tree(String),
+}
+
+#[derive(Default)]
+struct Options {
+    command: Option<Command>,
+    export: Option<Format>,
+    scope: Option<Scope>,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut bag = || args.next().ok_or("Missing bag");
+
+        match &arg[..] {
+            "contains" => options.command = Some(Command::Contains(bag()?)),
+            "containers" => options.command = Some(Command::Containers(bag()?)),
+            "path" => options.command = Some(Command::Path(bag()?, bag()?)),
+            "rules" => options.command = Some(Command::Rules),
+            "tree" => options.command = Some(Command::Tree(bag()?)),
+            "--export" => {
+                let format = args.next().ok_or("Missing export format")?;
+                options.export = Some(format.parse()?);
+            }
+            "--from" => options.scope = Some(Scope::From(bag()?)),
+            "--to" => options.scope = Some(Scope::To(bag()?)),
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+
+    if options.export.is_some() && options.command.is_some() {
+        return Err("--export cannot be combined with a command".to_owned());
+    }
+
+    if options.scope.is_some() && options.export.is_none() {
+        return Err("--from and --to require --export".to_owned());
+    }
+
+    Ok(options)
+}
 
+fn read_rules() -> io::Result<Vec<Rule>> {
     let stdin = io::stdin();
-    let mut graph: Graph = HashMap::new();
+    let mut rules = vec![];
 
-    for line in stdin.lock().lines() {
+    for (ii, line) in stdin.lock().lines().enumerate() {
         let line = line?;
 
-        let cap = outer_re.captures(&line).unwrap();
-        let outer = cap[1].to_owned();
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let rule = parse::parse_rule(ii + 1, &line)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        rules.push(rule);
+    }
+
+    Ok(rules)
+}
+
+fn puzzle_1(bag: &str, bags: &Bags) -> usize {
+    bags.id(bag).map_or(0, |id| bags.containers(id).len())
+}
+
+fn puzzle_2(bag: &str, bags: &Bags) -> Result<u64, CountError> {
+    match bags.id(bag) {
+        None => Ok(0),
+        Some(id) => bags.count_bags(id).map(|n| n - 1),
+    }
+}
 
-        graph.entry(outer.clone()).or_insert(vec![]);
+fn run(command: &Command, graph: &Graph, bags: &Bags) -> Result<(), String> {
+    let names = match command {
+        Command::Contains(bag) | Command::Containers(bag) | Command::Tree(bag) => vec![bag],
+        Command::Path(from, to) => vec![from, to],
+        Command::Rules => vec![],
+    };
 
-        for cap in inner_re.captures_iter(&line) {
-            let number = cap[1].parse::<u32>().unwrap();
-            let inner = cap[2].to_owned();
+    if let Some(bag) = names.iter().find(|b| bags.id(b).is_none()) {
+        return Err(format!("Unknown bag: {}", bag));
+    }
 
-            graph.get_mut(&outer).unwrap().push((inner, number));
+    match command {
+        Command::Contains(bag) => {
+            let n = puzzle_2(bag, bags).map_err(|e| e.to_string())?;
+            println!("{} bags contain {} other bags", bag, n);
         }
+        Command::Containers(bag) => {
+            for id in bags.containers(bags.id(bag).unwrap()) {
+                println!("{}", bags.name(id));
+            }
+        }
+        Command::Path(from, to) => {
+            match query::path(bags.id(from).unwrap(), bags.id(to).unwrap(), bags) {
+                Some(path) => {
+                    let chain: Vec<_> = path
+                        .iter()
+                        .map(|(b, n)| format!("{} {}", n, bags.name(*b)))
+                        .collect();
+                    println!("{}", chain.join(" -> "));
+                }
+                None => return Err(format!("{} bags cannot contain {} bags", from, to)),
+            }
+        }
+        Command::Rules => print!("{}", parse::write_rules(graph)),
+        Command::Tree(bag) => print!("{}", query::tree(bags.id(bag).unwrap(), bags)),
     }
 
-    Ok(graph)
+    Ok(())
 }
 
-fn is_reachable(from: &str, target: &str, graph: &Graph) -> bool {
-    let mut visited: HashSet<&str> = HashSet::new();
-    let mut queue: VecDeque<&str> = VecDeque::new();
-    queue.push_back(from);
-
-    loop {
-        let current = match queue.pop_front() {
-            None => break,
-            Some(c) if c == target => return true,
-            Some(c) if visited.contains(c) => continue,
-            Some(c) => c,
+fn main() -> io::Result<()> {
+    let options = parse_args(env::args().skip(1))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let rules = match read_rules() {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let graph = match validate::validate(rules) {
+        Ok(graph) => graph,
+        Err(errors) => {
+            for e in errors {
+                eprintln!("error: {}", e);
+            }
+            process::exit(1);
+        }
+    };
+
+    let bags = Bags::new(&graph);
+
+    if let Some(format) = options.export {
+        let highlight = match &options.scope {
+            Some(Scope::From(bag) | Scope::To(bag)) => bag,
+            None => TARGET,
         };
 
-        visited.insert(current);
+        print!(
+            "{}",
+            export::render(&bags, format, options.scope.as_ref(), highlight)
+        );
+        return Ok(());
+    }
 
-        if let Some(nodes) = graph.get(current) {
-            for (node, _) in nodes {
-                queue.push_back(&node);
-            }
+    if let Some(command) = &options.command {
+        if let Err(e) = run(command, &graph, &bags) {
+            eprintln!("error: {}", e);
+            process::exit(1);
         }
+        return Ok(());
     }
 
-    false
-}
+    println!("puzzle #1 = {:?}", puzzle_1(TARGET, &bags));
 
-fn count_bags(from: &str, graph: &Graph) -> u32 {
-    match graph.get(from) {
-        None => 1,
-        Some(bags) => bags
-            .iter()
-            .fold(1, |acc, (b, n)| acc + n * count_bags(b, graph)),
+    match puzzle_2(TARGET, &bags) {
+        Ok(n) => println!("puzzle #2 = {:?}", n),
+        Err(e) => eprintln!("error: {}", e),
     }
-}
 
-fn puzzle_1(bag: &str, graph: &Graph) -> usize {
-    graph
-        .keys()
-        .filter(|k| *k != bag)
-        .map(|k| is_reachable(k, bag, &graph))
-        .filter(|r| *r)
-        .count()
+    Ok(())
 }
 
-fn puzzle_2(bag: &str, graph: &Graph) -> u32 {
-    count_bags(bag, &graph) - 1
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-fn main() -> io::Result<()> {
-    let graph = read_graph()?;
-    println!("puzzle #1 = {:?}", puzzle_1(TARGET, &graph));
-    println!("puzzle #2 = {:?}", puzzle_2(TARGET, &graph));
-    Ok(())
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse(&["--export", "dot", "--from", "shiny gold"]).unwrap();
+        assert_eq!(options.export, Some(Format::Dot));
+        assert_eq!(options.scope, Some(Scope::From("shiny gold".to_owned())));
+        assert!(parse(&["tree", "shiny gold"]).unwrap().command.is_some());
+
+        assert!(parse(&["--from", "shiny gold"]).is_err());
+        assert!(parse(&["tree", "shiny gold", "--to", "faded blue"]).is_err());
+        assert!(parse(&["--export", "dot", "tree", "shiny gold"]).is_err());
+        assert!(parse(&["rules", "--export", "mermaid"]).is_err());
+        assert!(parse(&["--export", "svg"]).is_err());
+        assert!(parse(&["tree"]).is_err());
+        assert!(parse(&["shiny gold"]).is_err());
+    }
 }
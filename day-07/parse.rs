@@ -0,0 +1,298 @@
+use crate::graph::{Graph, Rule};
+use std::fmt::{self, Write};
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub reason: &'static str,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.reason
+        )
+    }
+}
+
+// cursor over a single rule:
+//
+//   rule     = bag " contain " contents ["."]
+//   contents = "no other bags" | item {", " item}
+//   item     = number " " bag
+//   bag      = word {" " word} " " ("bag" | "bags")
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, reason: &'static str) -> ParseError {
+        ParseError {
+            reason,
+            line: self.line,
+            column: self.text[..self.pos].chars().count() + 1,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn eat(&mut self, literal: &str) -> bool {
+        if self.rest().starts_with(literal) {
+            self.pos += literal.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, literal: &str, reason: &'static str) -> Result<(), ParseError> {
+        if self.eat(literal) {
+            Ok(())
+        } else {
+            Err(self.error(reason))
+        }
+    }
+
+    fn word(&mut self) -> Option<&'a str> {
+        let rest = self.rest();
+        let len = rest.find(&[' ', ',', '.'][..]).unwrap_or(rest.len());
+        self.pos += len;
+
+        if len == 0 {
+            None
+        } else {
+            Some(&rest[..len])
+        }
+    }
+
+    fn number(&mut self) -> Result<u32, ParseError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(rest.len());
+
+        if len == 0 {
+            return Err(self.error("expected a number"));
+        }
+
+        let n = rest[..len]
+            .parse()
+            .map_err(|_| self.error("number is too large"))?;
+
+        self.pos += len;
+        Ok(n)
+    }
+
+    fn bag(&mut self) -> Result<String, ParseError> {
+        let mut words = vec![];
+
+        loop {
+            match self.word() {
+                Some("bag") | Some("bags") if !words.is_empty() => return Ok(words.join(" ")),
+                Some(word) => words.push(word),
+                None if words.is_empty() => return Err(self.error("expected a bag color")),
+                None => return Err(self.error("expected `bag` or `bags`")),
+            }
+
+            self.expect(" ", "expected `bag` or `bags`")?;
+        }
+    }
+
+    fn rule(&mut self) -> Result<Rule, ParseError> {
+        let outer = self.bag()?;
+        self.expect(" contain ", "expected `contain`")?;
+        let mut inner = vec![];
+
+        if !self.eat("no other bags") {
+            loop {
+                let n = self.number()?;
+                self.expect(" ", "expected a space after the number")?;
+                inner.push((self.bag()?, n));
+
+                if !self.eat(", ") {
+                    break;
+                }
+            }
+        }
+
+        self.eat(".");
+
+        if !self.rest().is_empty() {
+            return Err(self.error("unexpected text after the rule"));
+        }
+
+        Ok(Rule {
+            line: self.line,
+            outer,
+            inner,
+        })
+    }
+}
+
+// a single rule on line `line`, ignoring trailing whitespace
+pub fn parse_rule(line: usize, text: &str) -> Result<Rule, ParseError> {
+    let mut parser = Parser {
+        text: text.trim_end(),
+        pos: 0,
+        line,
+    };
+
+    parser.rule()
+}
+
+// one rule per line, in name order, so that `parse_rule` reads back the same graph
+pub fn write_rules(graph: &Graph) -> String {
+    let mut bags: Vec<_> = graph.iter().collect();
+    bags.sort_unstable_by_key(|(bag, _)| *bag);
+    let mut out = String::new();
+
+    for (bag, inner) in bags {
+        let contents: Vec<_> = inner
+            .iter()
+            .map(|(b, n)| format!("{} {} {}", n, b, if *n == 1 { "bag" } else { "bags" }))
+            .collect();
+
+        let contents = if contents.is_empty() {
+            "no other bags".to_owned()
+        } else {
+            contents.join(", ")
+        };
+
+        writeln!(out, "{} bags contain {}.", bag, contents).unwrap();
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph;
+    use proptest::prelude::*;
+
+    fn rule(outer: &str, inner: &[(&str, u32)]) -> Result<Rule, ParseError> {
+        Ok(graph::rules(&[(outer, inner)]).remove(0))
+    }
+
+    fn error(reason: &'static str, column: usize) -> Result<Rule, ParseError> {
+        Err(ParseError {
+            reason,
+            line: 1,
+            column,
+        })
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!(
+            parse_rule(
+                1,
+                "light red bags contain 1 bright white bag, 2 muted yellow bags."
+            ),
+            rule("light red", &[("bright white", 1), ("muted yellow", 2)])
+        );
+        assert_eq!(
+            parse_rule(1, "faded blue bags contain no other bags.\r\n"),
+            rule("faded blue", &[])
+        );
+        assert_eq!(
+            parse_rule(1, "red bag contain 11 very dark blue bag"),
+            rule("red", &[("very dark blue", 11)])
+        );
+        assert_eq!(
+            parse_rule(1, "bags bags contain 2 bag bags"),
+            rule("bags", &[("bag", 2)])
+        );
+    }
+
+    #[test]
+    fn test_parse_rule_errors() {
+        assert_eq!(parse_rule(1, ""), error("expected a bag color", 1));
+        assert_eq!(
+            parse_rule(1, "light red"),
+            error("expected `bag` or `bags`", 10)
+        );
+        assert_eq!(
+            parse_rule(1, "light red bags contains no other bags."),
+            error("expected `contain`", 15)
+        );
+        assert_eq!(
+            parse_rule(1, "light red bags contain no bags."),
+            error("expected a number", 24)
+        );
+        assert_eq!(
+            parse_rule(1, "light red bags contain 1 white bag 2 blue bags."),
+            error("unexpected text after the rule", 35)
+        );
+        assert_eq!(
+            parse_rule(1, "light red bags contain 99999999999 white bags."),
+            error("number is too large", 24)
+        );
+        assert_eq!(
+            parse_rule(1, "rød bags contain 1 ü bag, ."),
+            error("expected a number", 27)
+        );
+        assert_eq!(
+            parse_rule(7, "a bags contain 1 bag.")
+                .unwrap_err()
+                .to_string(),
+            "line 7, column 21: expected `bag` or `bags`"
+        );
+    }
+
+    #[test]
+    fn test_write_rules() {
+        let graph: Graph = vec![
+            ("shiny gold".to_owned(), vec![("faded blue".to_owned(), 1)]),
+            ("faded blue".to_owned(), vec![]),
+            (
+                "light red".to_owned(),
+                vec![("shiny gold".to_owned(), 2), ("faded blue".to_owned(), 1)],
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            write_rules(&graph),
+            concat!(
+                "faded blue bags contain no other bags.\n",
+                "light red bags contain 2 shiny gold bags, 1 faded blue bag.\n",
+                "shiny gold bags contain 1 faded blue bag.\n",
+            )
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_parse_rule(raw in any::<String>()) {
+            let _ = parse_rule(1, &raw);
+        }
+
+        // colors never contain a `b` so that no color word can be `bag` or `bags`
+        #[test]
+        fn fuzz_write_rules_round_trip(
+            graph in prop::collection::hash_map(
+                "[c-z]{1,6} [c-z]{1,6}",
+                prop::collection::vec(("[c-z]{1,6}( [c-z]{1,6})?", any::<u32>()), 0..4),
+                0..8,
+            )
+        ) {
+            let parsed: Graph = write_rules(&graph)
+                .lines()
+                .enumerate()
+                .map(|(ii, line)| parse_rule(ii + 1, line).map(|r| (r.outer, r.inner)))
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+            prop_assert_eq!(parsed, graph);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use crate::bags::{BagId, Bags};
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+// shortest chain of bags leading from `from` to `to`, with the number of each bag inside
+// the previous one; the chain starts with a single `from` bag
+pub fn path(from: BagId, to: BagId, bags: &Bags) -> Option<Vec<(BagId, u32)>> {
+    // each visited bag, with the bag it was first found in
+    let mut parents: Vec<Option<Option<(BagId, u32)>>> = bags.ids().map(|_| None).collect();
+    let mut queue = VecDeque::new();
+
+    parents[from.index()] = Some(None);
+    queue.push_back(from);
+
+    while let Some(bag) = queue.pop_front() {
+        if bag == to {
+            let mut chain = vec![];
+            let mut current = (bag, 1);
+
+            while let Some(Some(parent)) = parents[current.0.index()] {
+                chain.push((current.0, parent.1));
+                current = parent;
+            }
+
+            chain.push((from, 1));
+            chain.reverse();
+            return Some(chain);
+        }
+
+        for (inner, n) in bags.inner(bag) {
+            if parents[inner.index()].is_none() {
+                parents[inner.index()] = Some(Some((bag, *n)));
+                queue.push_back(*inner);
+            }
+        }
+    }
+
+    None
+}
+
+// nested contents of `bag`, one line per inner bag
+// using an explicit stack so deep graphs do not overflow the call stack
+pub fn tree(bag: BagId, bags: &Bags) -> String {
+    let mut out = format!("{}\n", bags.name(bag));
+    let mut prefix = String::new();
+    // each frame is a bag, the length of its prefix and the index of its next inner bag
+    let mut stack = vec![(bag, 0, 0)];
+
+    while let Some((bag, len, ii)) = stack.pop() {
+        let inner = bags.inner(bag);
+        prefix.truncate(len);
+
+        let (inner_bag, n) = match inner.get(ii) {
+            Some(inner_bag) => inner_bag,
+            None => continue,
+        };
+
+        let (branch, indent) = if ii + 1 == inner.len() {
+            ("└──", "    ")
+        } else {
+            ("├──", "│   ")
+        };
+
+        writeln!(out, "{}{} {} {}", prefix, branch, n, bags.name(*inner_bag)).unwrap();
+        stack.push((bag, len, ii + 1));
+        prefix.push_str(indent);
+        stack.push((*inner_bag, prefix.len(), 0));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph;
+
+    fn bags() -> Bags {
+        let rules: &[(&str, &[(&str, u32)])] = &[
+            ("light red", &[("bright white", 1), ("muted yellow", 2)]),
+            ("bright white", &[("shiny gold", 1)]),
+            ("muted yellow", &[("shiny gold", 2), ("faded blue", 9)]),
+            ("shiny gold", &[("dark olive", 1), ("vibrant plum", 2)]),
+            ("dark olive", &[("faded blue", 3), ("dotted black", 4)]),
+            ("vibrant plum", &[("faded blue", 5)]),
+            ("faded blue", &[]),
+            ("dotted black", &[]),
+        ];
+
+        Bags::new(&graph::graph(rules))
+    }
+
+    fn path_names(from: &str, to: &str) -> Option<Vec<(String, u32)>> {
+        let bags = bags();
+        let path = path(bags.id(from).unwrap(), bags.id(to).unwrap(), &bags)?;
+        Some(
+            path.into_iter()
+                .map(|(b, n)| (bags.name(b).to_owned(), n))
+                .collect(),
+        )
+    }
+
+    fn tree_of(bag: &str) -> String {
+        let bags = bags();
+        tree(bags.id(bag).unwrap(), &bags)
+    }
+
+    #[test]
+    fn test_path() {
+        let path =
+            |bags: &[(&str, u32)]| Some(bags.iter().map(|(b, n)| (b.to_string(), *n)).collect());
+
+        assert_eq!(
+            path_names("light red", "dotted black"),
+            path(&[
+                ("light red", 1),
+                ("bright white", 1),
+                ("shiny gold", 1),
+                ("dark olive", 1),
+                ("dotted black", 4),
+            ])
+        );
+        assert_eq!(
+            path_names("muted yellow", "faded blue"),
+            path(&[("muted yellow", 1), ("faded blue", 9)])
+        );
+        assert_eq!(
+            path_names("faded blue", "faded blue"),
+            path(&[("faded blue", 1)])
+        );
+        assert_eq!(path_names("shiny gold", "light red"), None);
+    }
+
+    #[test]
+    fn test_tree() {
+        assert_eq!(
+            tree_of("shiny gold"),
+            concat!(
+                "shiny gold\n",
+                "├── 1 dark olive\n",
+                "│   ├── 3 faded blue\n",
+                "│   └── 4 dotted black\n",
+                "└── 2 vibrant plum\n",
+                "    └── 5 faded blue\n",
+            )
+        );
+        assert_eq!(tree_of("faded blue"), "faded blue\n");
+    }
+
+    #[test]
+    fn test_tree_deep() {
+        // a stack this small cannot hold one call frame per level of the chain
+        let tree = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(|| {
+                let bags = Bags::new(&graph::chain(5_000, 1));
+                tree(bags.id("0").unwrap(), &bags)
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(tree.lines().count(), 5_000);
+        assert_eq!(
+            tree.lines().last(),
+            Some(format!("{}└── 1 4999", "    ".repeat(4_998)).as_str())
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use crate::graph::{Graph, Rule};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum RuleError {
+    // the same rule is given more than once
+    Duplicate {
+        bag: String,
+        line: usize,
+        first_line: usize,
+    },
+    // a bag is given different contents on different lines
+    Conflict {
+        bag: String,
+        line: usize,
+        first_line: usize,
+    },
+    // a bag is contained in another one but has no rule of its own
+    Undefined {
+        bag: String,
+        line: usize,
+    },
+    // a bag eventually contains itself, as the path from that bag back to itself
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::Duplicate {
+                bag,
+                line,
+                first_line,
+            } => write!(
+                f,
+                "line {}: duplicate rule for {} bags (first given on line {})",
+                line, bag, first_line
+            ),
+            RuleError::Conflict {
+                bag,
+                line,
+                first_line,
+            } => write!(
+                f,
+                "line {}: rule for {} bags conflicts with line {}",
+                line, bag, first_line
+            ),
+            RuleError::Undefined { bag, line } => {
+                write!(f, "line {}: {} bags are never defined", line, bag)
+            }
+            RuleError::Cycle(path) => write!(f, "bags contain themselves: {}", path.join(" -> ")),
+        }
+    }
+}
+
+fn same_contents(a: &[(String, u32)], b: &[(String, u32)]) -> bool {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort();
+    b.sort();
+    a == b
+}
+
+// first cycle found when visiting bags in name order, starting and ending with the same bag
+fn find_cycle(graph: &Graph) -> Option<Vec<String>> {
+    // `false` while the bag is on the current path, `true` once all its contents are visited
+    let mut visited: HashMap<&str, bool> = HashMap::new();
+    let mut roots: Vec<_> = graph.keys().collect();
+    roots.sort();
+
+    for root in roots {
+        if visited.contains_key(&root[..]) {
+            continue;
+        }
+
+        // each bag on the current path, with the index of the next inner bag to visit
+        let mut path = vec![(&root[..], 0)];
+        visited.insert(root, false);
+
+        while let Some(&(bag, ii)) = path.last() {
+            let inner = graph.get(bag).and_then(|bags| bags.get(ii));
+
+            let inner = match inner {
+                Some((inner, _)) => &inner[..],
+                None => {
+                    visited.insert(bag, true);
+                    path.pop();
+                    continue;
+                }
+            };
+
+            path.last_mut().unwrap().1 += 1;
+
+            match visited.get(inner) {
+                Some(true) => {}
+                Some(false) => {
+                    let start = path.iter().position(|(b, _)| *b == inner).unwrap();
+                    let mut cycle: Vec<_> =
+                        path[start..].iter().map(|(b, _)| b.to_string()).collect();
+                    cycle.push(inner.to_owned());
+                    return Some(cycle);
+                }
+                None => {
+                    visited.insert(inner, false);
+                    path.push((inner, 0));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// checks the rules for duplicates, conflicts, undefined bags and cycles, and builds the
+// graph only if there are none
+pub fn validate(rules: Vec<Rule>) -> Result<Graph, Vec<RuleError>> {
+    let mut errors = vec![];
+    let mut defined: HashMap<String, Rule> = HashMap::new();
+
+    for rule in rules {
+        match defined.get(&rule.outer) {
+            Some(first) if same_contents(&first.inner, &rule.inner) => {
+                errors.push(RuleError::Duplicate {
+                    bag: rule.outer,
+                    line: rule.line,
+                    first_line: first.line,
+                })
+            }
+            Some(first) => errors.push(RuleError::Conflict {
+                bag: rule.outer,
+                line: rule.line,
+                first_line: first.line,
+            }),
+            None => {
+                defined.insert(rule.outer.clone(), rule);
+            }
+        }
+    }
+
+    let mut rules: Vec<_> = defined.values().collect();
+    rules.sort_by_key(|r| r.line);
+    let mut undefined = HashSet::new();
+
+    for rule in rules {
+        for (inner, _) in &rule.inner {
+            if !defined.contains_key(inner) && undefined.insert(inner) {
+                errors.push(RuleError::Undefined {
+                    bag: inner.clone(),
+                    line: rule.line,
+                });
+            }
+        }
+    }
+
+    let graph: Graph = defined
+        .into_iter()
+        .map(|(bag, rule)| (bag, rule.inner))
+        .collect();
+
+    if let Some(cycle) = find_cycle(&graph) {
+        errors.push(RuleError::Cycle(cycle));
+    }
+
+    if errors.is_empty() {
+        Ok(graph)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::rules;
+
+    #[test]
+    fn test_validate() {
+        let graph = validate(rules(&[
+            ("light red", &[("bright white", 1), ("muted yellow", 2)]),
+            ("bright white", &[("muted yellow", 1)]),
+            ("muted yellow", &[]),
+        ]))
+        .unwrap();
+
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph["bright white"], vec![("muted yellow".to_owned(), 1)]);
+    }
+
+    #[test]
+    fn test_validate_definitions() {
+        let errors = validate(rules(&[
+            ("light red", &[("bright white", 1), ("muted yellow", 2)]),
+            ("bright white", &[("shiny gold", 1)]),
+            ("light red", &[("muted yellow", 2), ("bright white", 1)]),
+            ("bright white", &[("shiny gold", 2)]),
+            ("muted yellow", &[("shiny gold", 3)]),
+        ]))
+        .unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                RuleError::Duplicate {
+                    bag: "light red".to_owned(),
+                    line: 3,
+                    first_line: 1,
+                },
+                RuleError::Conflict {
+                    bag: "bright white".to_owned(),
+                    line: 4,
+                    first_line: 2,
+                },
+                RuleError::Undefined {
+                    bag: "shiny gold".to_owned(),
+                    line: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            errors[2].to_string(),
+            "line 2: shiny gold bags are never defined"
+        );
+    }
+
+    #[test]
+    fn test_validate_cycle() {
+        let errors = validate(rules(&[
+            ("a", &[("b", 1)]),
+            ("b", &[("c", 1), ("d", 2)]),
+            ("c", &[]),
+            ("d", &[("b", 1)]),
+        ]))
+        .unwrap_err();
+
+        let path = ["b", "d", "b"].iter().map(|b| b.to_string()).collect();
+        assert_eq!(errors, vec![RuleError::Cycle(path)]);
+        assert_eq!(
+            errors[0].to_string(),
+            "bags contain themselves: b -> d -> b"
+        );
+
+        let errors = validate(rules(&[("a", &[("a", 1)])])).unwrap_err();
+        assert_eq!(errors[0].to_string(), "bags contain themselves: a -> a");
+    }
+}
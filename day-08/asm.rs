@@ -0,0 +1,204 @@
+use crate::{Op, Program};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+#[derive(Debug, PartialEq)]
+pub struct AsmError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+fn is_label(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+// one instruction per line, e.g. `acc +1`; anything after `#` is a comment, and an
+// instruction may be preceded by labels (`loop: jmp @loop`), which `jmp` and `nop` can
+// take instead of a relative offset
+pub fn assemble(source: &str) -> Result<Program, AsmError> {
+    let mut labels = HashMap::new();
+    // each instruction, with its line number
+    let mut lines = vec![];
+
+    for (ii, raw) in source.lines().enumerate() {
+        let line = ii + 1;
+        let error = |reason| AsmError { line, reason };
+        let mut text = raw.split('#').next().unwrap().trim();
+
+        while let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+
+            if !is_label(label) {
+                return Err(error(format!("invalid label `{}`", label)));
+            }
+
+            if labels.insert(label, lines.len()).is_some() {
+                return Err(error(format!("duplicate label `{}`", label)));
+            }
+
+            text = rest.trim();
+        }
+
+        if !text.is_empty() {
+            lines.push((line, text));
+        }
+    }
+
+    let mut program = vec![];
+
+    for (pc, (line, text)) in lines.into_iter().enumerate() {
+        let error = |reason| AsmError { line, reason };
+
+        let op = match text.split_whitespace().collect::<Vec<_>>()[..] {
+            [mnemonic @ ("jmp" | "nop"), operand] if operand.starts_with('@') => {
+                let target = labels
+                    .get(&operand[1..])
+                    .ok_or_else(|| error(format!("undefined label `{}`", &operand[1..])))?;
+
+                Op::new(mnemonic, (*target as i64 - pc as i64) as i32).unwrap()
+            }
+            _ => Op::parse(text).map_err(error)?,
+        };
+
+        program.push(op);
+    }
+
+    Ok(program)
+}
+
+// listing of `program` with the address of each instruction and the target of each jump
+pub fn disassemble(program: &[Op]) -> String {
+    let width = program.len().saturating_sub(1).to_string().len();
+    let mut out = String::new();
+
+    for (pc, op) in program.iter().enumerate() {
+        write!(out, "{:>width$}  {}", pc, op, width = width).unwrap();
+
+        if let Op::Jmp(n) = op {
+            let target = pc as i64 + *n as i64;
+
+            if target == program.len() as i64 {
+                write!(out, "  # -> end").unwrap();
+            } else if target < 0 || target > program.len() as i64 {
+                write!(out, "  # -> {} (out of bounds)", target).unwrap();
+            } else {
+                write!(out, "  # -> {}", target).unwrap();
+            }
+        }
+
+        writeln!(out).unwrap();
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::op;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_assemble() {
+        let source = "\
+            # counts down from 3\n\
+            acc +3\n\
+            \n\
+            loop: acc -1   # one less\n\
+            nop @end\n\
+            jmp @loop\n\
+            end:\n";
+
+        assert_eq!(
+            assemble(source),
+            Ok(vec![Op::Acc(3), Op::Acc(-1), Op::Nop(2), Op::Jmp(-2)])
+        );
+        assert_eq!(
+            assemble("a: b:jmp @a\nc: jmp @b"),
+            Ok(vec![Op::Jmp(0), Op::Jmp(-1)])
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let error = |line, reason: &str| {
+            Err(AsmError {
+                line,
+                reason: reason.to_owned(),
+            })
+        };
+
+        assert_eq!(
+            assemble("nop +0\nmul +2"),
+            error(2, "Unexpected input: mul +2")
+        );
+        assert_eq!(
+            assemble("a: nop +0\na: jmp @a"),
+            error(2, "duplicate label `a`")
+        );
+        assert_eq!(
+            assemble("jmp @nowhere"),
+            error(1, "undefined label `nowhere`")
+        );
+        assert_eq!(assemble("acc @a\na:"), error(1, "Unexpected input: acc @a"));
+        assert_eq!(
+            assemble("my label: nop +0"),
+            error(1, "invalid label `my label`")
+        );
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let program = vec![
+            Op::Nop(0),
+            Op::Acc(1),
+            Op::Jmp(4),
+            Op::Acc(3),
+            Op::Jmp(-5),
+            Op::Acc(-99),
+            Op::Jmp(5),
+            Op::Acc(1),
+            Op::Jmp(-4),
+            Op::Jmp(2),
+        ];
+
+        assert_eq!(
+            disassemble(&program),
+            concat!(
+                "0  nop +0\n",
+                "1  acc +1\n",
+                "2  jmp +4  # -> 6\n",
+                "3  acc +3\n",
+                "4  jmp -5  # -> -1 (out of bounds)\n",
+                "5  acc -99\n",
+                "6  jmp +5  # -> 11 (out of bounds)\n",
+                "7  acc +1\n",
+                "8  jmp -4  # -> 4\n",
+                "9  jmp +2  # -> 11 (out of bounds)\n",
+            )
+        );
+        assert_eq!(disassemble(&[Op::Jmp(1)]), "0  jmp +1  # -> end\n");
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_assemble(source in any::<String>()) {
+            let _ = assemble(&source);
+        }
+
+        #[test]
+        fn fuzz_display_round_trip(program in prop::collection::vec(op(any::<i32>()), 0..20)) {
+            let source: Vec<_> = program.iter().map(Op::to_string).collect();
+            prop_assert_eq!(assemble(&source.join("\n")), Ok(program));
+        }
+    }
+}
@@ -1,118 +1,215 @@
-use std::collections::BTreeSet;
-use std::io::{self, BufRead};
-
-#[derive(Clone, Debug)]
-enum Op {
+mod asm;
+mod repair;
+mod vm;
+
+use std::env;
+use std::fmt;
+use std::io::{self, Read};
+use std::process;
+use vm::{Halt, Vm};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
     Acc(i32),
     Jmp(i32),
     Nop(i32),
 }
 
 impl Op {
+    fn new(mnemonic: &str, n: i32) -> Option<Op> {
+        match mnemonic {
+            "acc" => Some(Op::Acc(n)),
+            "jmp" => Some(Op::Jmp(n)),
+            "nop" => Some(Op::Nop(n)),
+            _ => None,
+        }
+    }
+
     fn parse(raw: &str) -> Result<Op, String> {
-        let mut parts = raw.split_whitespace();
-
-        let err1 = || format!("Unexpected input: {}", raw);
-        let err2 = |_| format!("Unexpected input: {}", raw);
-
-        match parts.next().ok_or_else(err1)? {
-            op if op == "acc" || op == "jmp" || op == "nop" => {
-                let num = parts
-                    .next()
-                    .ok_or_else(err1)?
-                    .parse::<i32>()
-                    .map_err(err2)?;
-
-                match op {
-                    "acc" => Ok(Op::Acc(num)),
-                    "jmp" => Ok(Op::Jmp(num)),
-                    "nop" => Ok(Op::Nop(num)),
-                    _ => unreachable!(),
-                }
-            }
-            _ => return Err(err1()),
+        let err = || format!("Unexpected input: {}", raw);
+
+        match raw.split_whitespace().collect::<Vec<_>>()[..] {
+            [mnemonic, n] => Op::new(mnemonic, n.parse().map_err(|_| err())?).ok_or_else(err),
+            _ => Err(err()),
+        }
+    }
+
+    // `jmp` and `nop` swapped, or `None` for `acc`
+    fn swapped(self) -> Option<Op> {
+        match self {
+            Op::Acc(_) => None,
+            Op::Jmp(n) => Some(Op::Nop(n)),
+            Op::Nop(n) => Some(Op::Jmp(n)),
+        }
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Op::Acc(n) => write!(f, "acc {:+}", n),
+            Op::Jmp(n) => write!(f, "jmp {:+}", n),
+            Op::Nop(n) => write!(f, "nop {:+}", n),
         }
     }
 }
 
 type Program = Vec<Op>;
 
-#[derive(Debug)]
+// the example program from the puzzle description
+#[cfg(test)]
+const SAMPLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+#[cfg(test)]
+fn program(raw: &str) -> Program {
+    raw.lines().map(|l| Op::parse(l).unwrap()).collect()
+}
+
+// any instruction, with arguments drawn from `n`
+#[cfg(test)]
+fn op(
+    n: impl proptest::strategy::Strategy<Value = i32> + Clone,
+) -> impl proptest::strategy::Strategy<Value = Op> {
+    use proptest::prelude::*;
+
+    prop_oneof![
+        n.clone().prop_map(Op::Acc),
+        n.clone().prop_map(Op::Jmp),
+        n.prop_map(Op::Nop),
+    ]
+}
+
+#[derive(Debug, PartialEq)]
 enum Output {
+    // the program ran to just past its last instruction
     Success(i32),
     Cycle(i32),
+    // the jump at `pc` targets an address outside of the program
+    OutOfBounds { pc: usize, target: i64 },
+    // the `acc` at `pc` overflows the accumulator
+    Overflow { pc: usize },
 }
 
 fn run(program: &Program) -> Output {
-    let mut pc: usize = 0;
-    let mut acc: i32 = 0;
-    let mut visited = BTreeSet::new();
+    let mut vm = Vm::new(program);
+
+    match vm.run() {
+        Halt::Terminated => Output::Success(vm.acc()),
+        Halt::Cycle => Output::Cycle(vm.acc()),
+        Halt::OutOfBounds { pc, target } => Output::OutOfBounds { pc, target },
+        Halt::Overflow { pc } => Output::Overflow { pc },
+        halt => unreachable!("default halting conditions stopped at {:?}", halt),
+    }
+}
 
-    loop {
-        if pc == program.len() {
-            return Output::Success(acc);
-        }
+// prints every instruction run until the program halts
+fn print_trace(program: &Program) {
+    let mut vm = Vm::new(program);
+    let halt = vm.run();
+
+    for step in vm.trace() {
+        println!(
+            "{:>5}  {:<12} acc {} -> {}",
+            step.pc,
+            step.op.to_string(),
+            step.acc_before,
+            step.acc_after
+        );
+    }
 
-        if visited.contains(&pc) {
-            return Output::Cycle(acc);
-        }
+    println!("halted at {}: {:?}", vm.pc(), halt);
+}
 
-        visited.insert(pc);
+fn main() -> io::Result<()> {
+    let mut source = String::new();
+    io::stdin().read_to_string(&mut source)?;
 
-        match program[pc] {
-            Op::Acc(n) => {
-                acc += n;
-                pc += 1;
+    let program = match asm::assemble(&source) {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    for arg in env::args().skip(1) {
+        match &arg[..] {
+            "--trace" => print_trace(&program),
+            "--disassemble" => print!("{}", asm::disassemble(&program)),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unexpected argument: {}", arg),
+                ))
             }
-            Op::Jmp(n) => pc = (pc as i32 + n) as usize,
-            Op::Nop(_) => pc += 1,
         }
     }
-}
 
-fn iter_corrections(program: Program) -> impl Iterator<Item = Program> {
-    let mut pc = 0;
-
-    std::iter::from_fn(move || {
-        let mut program = program.clone();
-
-        while pc < program.len() {
-            match program[pc] {
-                Op::Acc(_) => pc += 1,
-                Op::Jmp(n) => {
-                    program[pc] = Op::Nop(n);
-                    pc += 1;
-                    return Some(program);
-                }
-                Op::Nop(n) => {
-                    program[pc] = Op::Jmp(n);
-                    pc += 1;
-                    return Some(program);
-                }
-            }
+    let output = run(&program);
+
+    match output {
+        Output::Cycle(acc) => println!("puzzle #1 = {:?}", acc),
+        Output::Success(acc) => eprintln!("program terminated without a cycle (acc = {})", acc),
+        Output::OutOfBounds { pc, target } => {
+            eprintln!("jump at {} leaves the program (target {})", pc, target)
         }
+        Output::Overflow { pc } => eprintln!("acc at {} overflows the accumulator", pc),
+    }
 
-        None
-    })
+    match (output, repair::repair(&program)) {
+        (Output::Cycle(_), Some((_, acc))) => println!("puzzle #2 = {:?}", acc),
+        (Output::Cycle(_), None) => {
+            eprintln!("no single jmp/nop swap makes the program terminate")
+        }
+        (Output::Success(_), _) => eprintln!("program already terminates, nothing to repair"),
+        _ => eprintln!("program does not loop, nothing to repair"),
+    }
+
+    Ok(())
 }
 
-fn main() {
-    let stdin = io::stdin();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let program: Program = stdin
-        .lock()
-        .lines()
-        .map(Result::unwrap)
-        .map(|l| Op::parse(&l))
-        .map(Result::unwrap)
-        .collect();
+    #[test]
+    fn test_run_success() {
+        assert_eq!(run(&program("acc +1\nnop +5\nacc +2")), Output::Success(3));
+        assert_eq!(run(&program("acc +1\njmp +2\nacc +2")), Output::Success(1));
+        assert_eq!(run(&program("jmp +2\njmp +2\njmp -1")), Output::Success(0));
+        assert_eq!(run(&vec![]), Output::Success(0));
+    }
 
-    println!("puzzle #1 = {:?}", run(&program));
+    #[test]
+    fn test_run_cycle() {
+        assert_eq!(run(&program("acc +1\njmp -1")), Output::Cycle(1));
+        assert_eq!(run(&program("jmp +0")), Output::Cycle(0));
+    }
 
-    for program in iter_corrections(program) {
-        if let Output::Success(acc) = run(&program) {
-            println!("puzzle #2 = {:?}", acc);
-            break;
-        }
+    #[test]
+    fn test_run_overflow() {
+        assert_eq!(
+            run(&program("acc +2147483647\nacc +1")),
+            Output::Overflow { pc: 1 }
+        );
+    }
+
+    #[test]
+    fn test_run_out_of_bounds() {
+        assert_eq!(
+            run(&program("acc +1\njmp -2")),
+            Output::OutOfBounds { pc: 1, target: -1 }
+        );
+        assert_eq!(
+            run(&program("acc +1\njmp +2")),
+            Output::OutOfBounds { pc: 1, target: 3 }
+        );
+        assert_eq!(
+            run(&program("jmp -2147483648")),
+            Output::OutOfBounds {
+                pc: 0,
+                target: i32::MIN as i64,
+            }
+        );
     }
 }
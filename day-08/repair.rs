@@ -0,0 +1,143 @@
+use crate::vm::{Halt, Vm};
+use crate::Op;
+
+// address `op` at `pc` moves to, if it is within the program or just past its end
+fn next(pc: usize, op: Op, len: usize) -> Option<usize> {
+    let target = match op {
+        Op::Jmp(n) => pc as i64 + n as i64,
+        Op::Acc(_) | Op::Nop(_) => pc as i64 + 1,
+    };
+
+    if (0..=len as i64).contains(&target) {
+        Some(target as usize)
+    } else {
+        None
+    }
+}
+
+// for every address (and the one just past the end), whether the unmodified program
+// terminates when started there, found by walking backwards from the end
+fn terminating(program: &[Op]) -> Vec<bool> {
+    let len = program.len();
+    let mut sources = vec![vec![]; len + 1];
+
+    for (pc, op) in program.iter().enumerate() {
+        if let Some(target) = next(pc, *op, len) {
+            sources[target].push(pc);
+        }
+    }
+
+    let mut found = vec![false; len + 1];
+    let mut stack = vec![len];
+    found[len] = true;
+
+    while let Some(pc) = stack.pop() {
+        for source in &sources[pc] {
+            if !found[*source] {
+                found[*source] = true;
+                stack.push(*source);
+            }
+        }
+    }
+
+    found
+}
+
+// swaps the first `jmp` or `nop` on the program's path whose other variant leads to an
+// address from which the program terminates, and whose repaired program runs without
+// overflowing the accumulator; returns the swapped address and the final accumulator of
+// the repaired program
+//
+// only instructions on the original path can fix a looping program, and the fixed path
+// never runs the swapped instruction twice, so its new target already terminates in the
+// unmodified program
+//
+// programs that do not loop, e.g. because they already terminate, are not repaired
+pub fn repair(program: &[Op]) -> Option<(usize, i32)> {
+    let len = program.len();
+    let terminating = terminating(program);
+    let mut visited = vec![false; len];
+    let mut pc = 0;
+
+    while pc < len && !visited[pc] {
+        visited[pc] = true;
+        let op = program[pc];
+
+        if let Some(swapped) = op.swapped() {
+            if next(pc, swapped, len).is_some_and(|target| terminating[target]) {
+                let mut repaired = program.to_vec();
+                repaired[pc] = swapped;
+
+                let mut vm = Vm::new(&repaired);
+                if vm.run() == Halt::Terminated {
+                    return Some((pc, vm.acc()));
+                }
+            }
+        }
+
+        pc = next(pc, op, len)?;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{op, program, run, Output, SAMPLE};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_repair() {
+        assert_eq!(repair(&program(SAMPLE)), Some((7, 8)));
+
+        // as a `jmp`, the `nop` at 0 would leave the program
+        assert_eq!(
+            repair(&[Op::Nop(-1), Op::Jmp(0), Op::Jmp(1), Op::Acc(1)]),
+            Some((1, 1))
+        );
+        assert_eq!(repair(&[Op::Acc(1), Op::Jmp(-1)]), Some((1, 1)));
+        assert_eq!(repair(&[Op::Acc(1), Op::Jmp(-1), Op::Jmp(-1)]), None);
+        assert_eq!(repair(&[Op::Acc(1), Op::Jmp(-3)]), Some((1, 1)));
+        assert_eq!(repair(&[Op::Acc(1)]), None);
+        assert_eq!(repair(&[]), None);
+
+        // swapping the `nop` at 1 or the `jmp` at 2 terminates, but overflows on the way
+        let overflowing = [
+            Op::Acc(i32::MAX),
+            Op::Nop(2),
+            Op::Jmp(3),
+            Op::Acc(1),
+            Op::Jmp(3),
+            Op::Jmp(-5),
+            Op::Jmp(1),
+        ];
+        assert_eq!(repair(&overflowing), Some((5, i32::MAX)));
+    }
+
+    proptest! {
+        #[test]
+        fn fuzz_repair(program in prop::collection::vec(op(-5..5), 0..30)) {
+            let looping = matches!(run(&program), Output::Cycle(_));
+
+            // any single swap that makes the program terminate
+            let fixable = (0..program.len()).any(|pc| match program[pc].swapped() {
+                None => false,
+                Some(swapped) => {
+                    let mut repaired = program.clone();
+                    repaired[pc] = swapped;
+                    matches!(run(&repaired), Output::Success(_))
+                }
+            });
+
+            match repair(&program) {
+                Some((pc, acc)) => {
+                    let mut repaired = program.clone();
+                    repaired[pc] = program[pc].swapped().unwrap();
+                    prop_assert_eq!(run(&repaired), Output::Success(acc));
+                }
+                None => prop_assert!(!(looping && fixable)),
+            }
+        }
+    }
+}
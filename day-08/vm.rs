@@ -0,0 +1,278 @@
+use crate::Op;
+use std::collections::BTreeSet;
+
+// a single executed instruction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Step {
+    pub pc: usize,
+    pub op: Op,
+    pub acc_before: i32,
+    pub acc_after: i32,
+}
+
+// conditions under which `Vm::run` stops before executing the next instruction
+#[derive(Clone, Debug)]
+pub struct Halting {
+    // stop when an instruction is about to run a second time
+    pub on_cycle: bool,
+    // stop when reaching one of these addresses, unless it is where `run` started
+    pub breakpoints: BTreeSet<usize>,
+    // stop after this many instructions in total
+    pub max_steps: Option<usize>,
+}
+
+impl Default for Halting {
+    fn default() -> Halting {
+        Halting {
+            on_cycle: true,
+            breakpoints: BTreeSet::new(),
+            max_steps: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Halt {
+    // the program counter moved to just past the last instruction
+    Terminated,
+    Cycle,
+    Breakpoint,
+    StepLimit,
+    // the jump at `pc` would move before the first instruction or further than just past
+    // the last one; the jump is not executed
+    OutOfBounds { pc: usize, target: i64 },
+    // the `acc` at `pc` would overflow the accumulator; it is not executed
+    Overflow { pc: usize },
+}
+
+pub struct Vm<'a> {
+    program: &'a [Op],
+    halting: Halting,
+    pc: usize,
+    acc: i32,
+    visited: Vec<bool>,
+    trace: Vec<Step>,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(program: &'a [Op]) -> Vm<'a> {
+        Vm::with_halting(program, Halting::default())
+    }
+
+    pub fn with_halting(program: &'a [Op], halting: Halting) -> Vm<'a> {
+        Vm {
+            program,
+            halting,
+            pc: 0,
+            acc: 0,
+            visited: vec![false; program.len()],
+            trace: vec![],
+        }
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn acc(&self) -> i32 {
+        self.acc
+    }
+
+    // all instructions executed so far, in order
+    pub fn trace(&self) -> &[Step] {
+        &self.trace
+    }
+
+    // executes the instruction at `pc`, ignoring the halting conditions; `pc` never moves
+    // further than just past the last instruction
+    pub fn step(&mut self) -> Result<Step, Halt> {
+        let op = *self.program.get(self.pc).ok_or(Halt::Terminated)?;
+        let acc_before = self.acc;
+        let pc = self.pc;
+
+        match op {
+            Op::Acc(n) => {
+                self.acc = self.acc.checked_add(n).ok_or(Halt::Overflow { pc })?;
+                self.pc += 1;
+            }
+            Op::Jmp(n) => {
+                let target = pc as i64 + n as i64;
+
+                if target < 0 || target > self.program.len() as i64 {
+                    return Err(Halt::OutOfBounds { pc, target });
+                }
+
+                self.pc = target as usize;
+            }
+            Op::Nop(_) => self.pc += 1,
+        }
+
+        let step = Step {
+            pc,
+            op,
+            acc_before,
+            acc_after: self.acc,
+        };
+
+        self.visited[pc] = true;
+        self.trace.push(step);
+        Ok(step)
+    }
+
+    // steps until the program terminates or one of the halting conditions is met
+    pub fn run(&mut self) -> Halt {
+        let start = self.trace.len();
+
+        loop {
+            let pc = self.pc;
+
+            if pc == self.program.len() {
+                return Halt::Terminated;
+            }
+
+            if self.halting.on_cycle && self.visited[pc] {
+                return Halt::Cycle;
+            }
+
+            if self.trace.len() > start && self.halting.breakpoints.contains(&pc) {
+                return Halt::Breakpoint;
+            }
+
+            if self
+                .halting
+                .max_steps
+                .is_some_and(|max| self.trace.len() >= max)
+            {
+                return Halt::StepLimit;
+            }
+
+            if let Err(halt) = self.step() {
+                return halt;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{program, SAMPLE};
+
+    #[test]
+    fn test_step() {
+        let program = program(SAMPLE);
+        let mut vm = Vm::new(&program);
+
+        assert_eq!(
+            vm.step(),
+            Ok(Step {
+                pc: 0,
+                op: Op::Nop(0),
+                acc_before: 0,
+                acc_after: 0,
+            })
+        );
+        assert_eq!(
+            vm.step(),
+            Ok(Step {
+                pc: 1,
+                op: Op::Acc(1),
+                acc_before: 0,
+                acc_after: 1,
+            })
+        );
+        vm.step().unwrap();
+        assert_eq!((vm.pc(), vm.acc()), (6, 1));
+        assert_eq!(vm.trace().len(), 3);
+
+        let program = vec![Op::Acc(5)];
+        let mut vm = Vm::new(&program);
+        assert!(vm.step().is_ok());
+        assert_eq!(vm.step(), Err(Halt::Terminated));
+        assert_eq!((vm.pc(), vm.acc()), (1, 5));
+    }
+
+    #[test]
+    fn test_run_cycle() {
+        let program = program(SAMPLE);
+        let mut vm = Vm::new(&program);
+
+        assert_eq!(vm.run(), Halt::Cycle);
+        assert_eq!((vm.pc(), vm.acc()), (1, 5));
+
+        let pcs: Vec<_> = vm.trace().iter().map(|s| s.pc).collect();
+        assert_eq!(pcs, vec![0, 1, 2, 6, 7, 3, 4]);
+    }
+
+    #[test]
+    fn test_run_halting() {
+        let program = program(SAMPLE);
+
+        let halting = Halting {
+            breakpoints: vec![3, 7].into_iter().collect(),
+            ..Halting::default()
+        };
+        let mut vm = Vm::with_halting(&program, halting);
+        assert_eq!(vm.run(), Halt::Breakpoint);
+        assert_eq!(vm.pc(), 7);
+        assert_eq!(vm.run(), Halt::Breakpoint);
+        assert_eq!(vm.pc(), 3);
+        assert_eq!(vm.run(), Halt::Cycle);
+
+        let halting = Halting {
+            on_cycle: false,
+            max_steps: Some(20),
+            ..Halting::default()
+        };
+        let mut vm = Vm::with_halting(&program, halting);
+        assert_eq!(vm.run(), Halt::StepLimit);
+        assert_eq!(vm.trace().len(), 20);
+        assert_eq!(vm.run(), Halt::StepLimit);
+        assert_eq!(vm.trace().len(), 20);
+
+        let program = vec![Op::Nop(0), Op::Jmp(-1)];
+        let mut vm = Vm::with_halting(&program, Halting::default());
+        assert_eq!(vm.run(), Halt::Cycle);
+        assert_eq!(vm.trace().len(), 2);
+    }
+
+    #[test]
+    fn test_step_out_of_bounds() {
+        let program = vec![Op::Acc(1), Op::Jmp(-2), Op::Jmp(2)];
+        let mut vm = Vm::new(&program);
+
+        vm.step().unwrap();
+        assert_eq!(vm.step(), Err(Halt::OutOfBounds { pc: 1, target: -1 }));
+        assert_eq!((vm.pc(), vm.acc()), (1, 1));
+        assert_eq!(vm.trace().len(), 1);
+
+        let mut vm = Vm::new(&program[2..]);
+        assert_eq!(vm.step(), Err(Halt::OutOfBounds { pc: 0, target: 2 }));
+        assert_eq!(vm.run(), Halt::OutOfBounds { pc: 0, target: 2 });
+    }
+
+    #[test]
+    fn test_step_overflow() {
+        let program = vec![Op::Acc(i32::MAX), Op::Acc(1)];
+        let mut vm = Vm::new(&program);
+
+        vm.step().unwrap();
+        assert_eq!(vm.step(), Err(Halt::Overflow { pc: 1 }));
+        assert_eq!((vm.pc(), vm.acc()), (1, i32::MAX));
+        assert_eq!(vm.trace().len(), 1);
+        assert_eq!(vm.run(), Halt::Overflow { pc: 1 });
+
+        let program = vec![Op::Acc(i32::MIN), Op::Acc(-1)];
+        assert_eq!(Vm::new(&program).run(), Halt::Overflow { pc: 1 });
+    }
+
+    #[test]
+    fn test_run_terminated() {
+        let mut program = program(SAMPLE);
+        program[7] = Op::Nop(-4);
+        let mut vm = Vm::new(&program);
+
+        assert_eq!(vm.run(), Halt::Terminated);
+        assert_eq!((vm.pc(), vm.acc()), (9, 8));
+    }
+}